@@ -27,7 +27,7 @@ pub enum ErrorCode {
     #[msg("two amounts that are supposed to be equal are not")]
     AmountMismatch,
 
-    #[msg("we do not currently support the variable rate reward type")]
+    #[msg("reward type doesn't match the config passed in")]
     InvalidRewardType,
 
     #[msg("invalid tier configuration. the deposit gem tier configuration must exist on the farm")]
@@ -41,7 +41,10 @@ pub enum ErrorCode {
 
     #[msg("already claimed reserved rewards")]
     AlreadyClaimedAllReservedRewards,
-    Reserved10,
+
+    #[msg("signer is not allowed to perform this operation")]
+    Unauthorized,
+
     Reserved11,
     Reserved12,
     Reserved13,
@@ -62,8 +65,12 @@ pub enum ErrorCode {
     #[msg("this gem is not present on any of the whitelists")]
     NotWhitelisted,
 
-    Reserved26,
-    Reserved27,
+    #[msg("merkle proof doesn't match the farm's allowlist root")]
+    InvalidMerkleProof, //0x143
+
+    #[msg("this gem has been denylisted by the farm")]
+    MintDenylisted, //0x144
+
     Reserved28,
     Reserved29,
     Reserved30,
@@ -79,7 +86,7 @@ pub enum ErrorCode {
 
     // --------------------------------------- farm specific (40 - 59)
     #[msg("passed in reward mint is not available for this farm")]
-    UnknownRewardMint, //0x154
+    UnknownRewardMint, //0x151
 
     #[msg("the reward is locked and cannot be cancelled")]
     RewardLocked,
@@ -90,21 +97,30 @@ pub enum ErrorCode {
     #[msg("can't unstake, cooldown period has not passed yet")]
     CooldownNotPassed,
 
-    Reserved44,
+    #[msg("unstaking hasn't begun yet, call begin_unstake first")]
+    UnstakeNotStarted,
 
     #[msg("reward has insufficient funding, please top up")]
-    RewardUnderfunded, //0x159
+    RewardUnderfunded, //0x156
 
     #[msg("update authority passed doesnt match that stored in metadata")]
-    WrongUpdateAuthority,
+    WrongUpdateAuthority, //0x157
 
     #[msg("wrong metadata account, gem mint doesn't match")]
     WrongMetadata,
 
-    Reserved48,
-    Reserved49,
-    Reserved50,
-    Reserved51,
+    #[msg("the reward period has ended, the farm no longer accepts deposits")]
+    RewardEnded,
+
+    #[msg("the tier's tenure runs past the end of the funded reward period")]
+    TenureExceedsRewardEnd,
+
+    #[msg("the farm is paused, only emergency withdrawals are allowed")]
+    FarmPaused, //0x15b
+
+    #[msg("vault has gems staked in place, unstake them before transferring it")]
+    GemsStakedInPlace, //0x15c
+
     Reserved52,
     Reserved53,
     Reserved54,
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct AcceptVaultOwner<'info> {
+    // vault
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    // has to match the vault's pending owner (checked in handler)
+    pub new_owner: Signer<'info>,
+}
+
+/// staked gems, their tenures and any accrued rewards all stay with the vault.
+/// gems staked in place have to be unstaked first - they sit in the previous owner's
+/// token accounts, and only that owner can sign off on thawing them
+pub fn handler(ctx: Context<AcceptVaultOwner>) -> ProgramResult {
+    let vault = &mut ctx.accounts.vault;
+    let previous_owner = vault.owner;
+
+    vault.accept_owner(&ctx.accounts.new_owner.key())?;
+
+    msg!(
+        "vault {} transferred from {} to {}",
+        vault.key(),
+        previous_owner,
+        vault.owner
+    );
+    Ok(())
+}
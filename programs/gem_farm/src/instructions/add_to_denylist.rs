@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct AddToDenylist<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(mut)]
+    pub farm_manager: Signer<'info>,
+
+    // denylist
+    pub mint_to_denylist: AccountInfo<'info>,
+    #[account(init_if_needed, seeds = [
+            b"denylist".as_ref(),
+            farm.key().as_ref(),
+            mint_to_denylist.key().as_ref(),
+        ],
+        bump = bump,
+        payer = farm_manager,
+        space = 8 + std::mem::size_of::<DenylistProof>())]
+    denylist_proof: Box<Account<'info, DenylistProof>>,
+
+    // misc
+    system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddToDenylist>) -> ProgramResult {
+    // create/update denylist proof
+    let proof = &mut ctx.accounts.denylist_proof;
+    proof.denylisted_mint = ctx.accounts.mint_to_denylist.key();
+    proof.farm = ctx.accounts.farm.key();
+
+    msg!("{} added to denylist", ctx.accounts.mint_to_denylist.key());
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use gem_common::errors::ErrorCode;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct AddToWhitelist<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(mut)]
+    pub farm_manager: Signer<'info>,
+
+    // whitelist
+    pub address_to_whitelist: AccountInfo<'info>,
+    #[account(init_if_needed, seeds = [
+            b"whitelist".as_ref(),
+            farm.key().as_ref(),
+            address_to_whitelist.key().as_ref(),
+        ],
+        bump = bump,
+        payer = farm_manager,
+        space = 8 + std::mem::size_of::<WhitelistProof>())]
+    whitelist_proof: Box<Account<'info, WhitelistProof>>,
+
+    // misc
+    system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddToWhitelist>, whitelist_type: u8) -> ProgramResult {
+    let whitelist_type =
+        WhitelistType::from_bits(whitelist_type).ok_or(ErrorCode::InvalidParameter)?;
+
+    if whitelist_type.is_empty() {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    let farm = &mut ctx.accounts.farm;
+    let proof = &mut ctx.accounts.whitelist_proof;
+
+    // re-whitelisting an address replaces its types, so counts are redone from scratch
+    farm.remove_whitelist_counts(proof.read_type()?)?;
+    farm.add_whitelist_counts(whitelist_type)?;
+
+    // create/update whitelist proof
+    proof.reset_type(whitelist_type);
+    proof.whitelisted_address = ctx.accounts.address_to_whitelist.key();
+    proof.farm = farm.key();
+
+    msg!(
+        "{} added to whitelist",
+        ctx.accounts.address_to_whitelist.key()
+    );
+    Ok(())
+}
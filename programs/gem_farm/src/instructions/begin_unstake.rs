@@ -0,0 +1,93 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke, system_instruction},
+};
+use gem_common::{errors::ErrorCode, *};
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump_treasury: u8)]
+pub struct BeginUnstake<'info> {
+    // farm
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    #[account(mut, seeds = [b"treasury".as_ref(), farm.key().as_ref()], bump = bump_treasury)]
+    pub farm_treasury: AccountInfo<'info>,
+
+    // vault
+    #[account(mut, has_one = farm, has_one = owner)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub owner: AccountInfo<'info>,
+
+    // the vault owner or its delegate (checked in handler), pays any paper hands tax
+    #[account(mut)]
+    pub unstaker: Signer<'info>,
+
+    #[account(mut, has_one = vault)]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    // misc
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BeginUnstake<'info> {
+    fn pay_treasury(&self, lamports: u64) -> ProgramResult {
+        invoke(
+            &system_instruction::transfer(self.unstaker.key, self.farm_treasury.key, lamports),
+            &[
+                self.unstaker.to_account_info(),
+                self.farm_treasury.clone(),
+                self.system_program.to_account_info(),
+            ],
+        )
+    }
+}
+
+pub fn handler(ctx: Context<BeginUnstake>) -> ProgramResult {
+    let farm = &*ctx.accounts.farm;
+    let staked_gem = &*ctx.accounts.staked_gem;
+
+    let now = now_ts()?;
+
+    ctx.accounts
+        .vault
+        .assert_owner_or_delegate(&ctx.accounts.unstaker.key())?;
+    ctx.accounts.vault.assert_not_frozen()?;
+
+    if staked_gem.attempting_to_break_bank(now)? {
+        // if there is a paper hands tax, charge the user
+        if farm.config.paper_hands_tax_lamp > 0 {
+            ctx.accounts
+                .pay_treasury(farm.config.paper_hands_tax_lamp)?;
+        } else {
+            return Err(ErrorCode::TooEarlyToWithdraw.into());
+        }
+    }
+
+    let farm = &mut ctx.accounts.farm;
+    let vault = &mut ctx.accounts.vault;
+    let staked_gem = &mut ctx.accounts.staked_gem;
+
+    // settle variable rewards up to now, nothing accrues to the gem past this point
+    farm.update_rewards(now, Some(staked_gem))?;
+
+    staked_gem.begin_unstake(now, farm.config.cooldown_sec)?;
+
+    // release whatever part of the reservations won't be earned now that the gem has
+    // stopped earning - if the tenures are over there's nothing left to release
+    farm.unreserve_rewards(staked_gem, now)?;
+
+    // and it no longer takes a share of variable rewards
+    let rarity_points = staked_gem.rarity_points;
+    farm.unstake_rarity_points(vault, staked_gem, rarity_points)?;
+
+    msg!(
+        "gem {} unstaking, can withdraw after {}",
+        staked_gem.gem_mint,
+        staked_gem.cooldown_ends_ts
+    );
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use gem_common::{errors::ErrorCode, *};
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump_auth: u8, bump_pot: u8)]
+pub struct CancelReward<'info> {
+    // farm
+    #[account(mut, has_one = farm_authority)]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(seeds = [farm.key().as_ref()], bump = bump_auth)]
+    pub farm_authority: AccountInfo<'info>,
+
+    // either the farm manager or an authorized funder
+    #[account(mut)]
+    pub canceller: Signer<'info>,
+
+    // reward
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_mint.key().as_ref(),
+        ],
+        bump = bump_pot)]
+    pub reward_pot: Box<Account<'info, TokenAccount>>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    // destination
+    #[account(init_if_needed,
+        associated_token::mint = reward_mint,
+        associated_token::authority = receiver,
+        payer = canceller)]
+    pub reward_destination: Box<Account<'info, TokenAccount>>,
+    pub receiver: AccountInfo<'info>,
+
+    // misc
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    //
+    // remaining accounts need to be passed if the canceller isn't the farm manager:
+    // - authorization_proof
+}
+
+impl<'info> CancelReward<'info> {
+    fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_pot.to_account_info(),
+                to: self.reward_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
+        )
+    }
+}
+
+fn assert_authorized_funder(ctx: &Context<CancelReward>) -> ProgramResult {
+    let farm = ctx.accounts.farm.key();
+    let funder = ctx.accounts.canceller.key();
+    let remaining_accs = &mut ctx.remaining_accounts.iter();
+
+    let proof_info = next_account_info(remaining_accs)?;
+
+    // verify the proof is the PDA issued by this farm to this funder
+    let (proof_addr, _bump) = Pubkey::find_program_address(
+        &[b"authorization".as_ref(), farm.as_ref(), funder.as_ref()],
+        ctx.program_id,
+    );
+    if proof_addr != proof_info.key() {
+        return Err(ErrorCode::Unauthorized.into());
+    }
+
+    // deserializing checks the owner + discriminator, ie that the proof hasn't been closed
+    let proof = Account::<AuthorizationProof>::try_from(proof_info)?;
+    if proof.farm != farm || proof.authorized_funder != funder {
+        return Err(ErrorCode::Unauthorized.into());
+    }
+
+    Ok(())
+}
+
+pub fn handler(ctx: Context<CancelReward>) -> ProgramResult {
+    if ctx.accounts.canceller.key() != ctx.accounts.farm.farm_manager {
+        assert_authorized_funder(&ctx)?;
+    }
+
+    let farm = &mut ctx.accounts.farm;
+
+    let now = now_ts()?;
+
+    let refund_amount = farm.cancel_reward_by_mint(now, ctx.accounts.reward_mint.key())?;
+
+    if refund_amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            refund_amount,
+        )?;
+    }
+
+    msg!(
+        "{} reward tokens refunded to {}",
+        refund_amount,
+        ctx.accounts.reward_destination.key()
+    );
+    Ok(())
+}
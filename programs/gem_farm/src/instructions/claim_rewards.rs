@@ -3,12 +3,12 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{self, Mint, Token, TokenAccount, Transfer},
 };
-use gem_common::*;
+use gem_common::{errors::ErrorCode, *};
 
 use crate::state::*;
 
 #[derive(Accounts)]
-#[instruction(bump_auth: u8, bump_vault: u8, bump_pot_a: u8)]
+#[instruction(bump_auth: u8, bump_vault: u8, bump_pot_a: u8, bump_pot_b: u8)]
 pub struct ClaimReward<'info> {
     // farm
     #[account(mut, has_one = farm_authority)]
@@ -21,16 +21,17 @@ pub struct ClaimReward<'info> {
     #[account(mut, has_one = farm, has_one = owner, seeds = [
             b"vault".as_ref(),
             farm.key().as_ref(),
-            owner.key().as_ref(),
-            gem_mint.key().as_ref(),
+            vault.creator.as_ref(),
         ],
         bump = bump_vault)]
     pub vault: Box<Account<'info, Vault>>,
 
-    pub gem_mint: Box<Account<'info, Mint>>,
+    // rewards always go to the owner's ATAs
+    pub owner: AccountInfo<'info>,
 
+    // the vault owner or its delegate (checked in handler)
     #[account(mut)] //payer
-    pub owner: Signer<'info>,
+    pub claimer: Signer<'info>,
 
     // reward a
     #[account(mut, seeds = [
@@ -46,14 +47,33 @@ pub struct ClaimReward<'info> {
     #[account(init_if_needed,
         associated_token::mint = reward_a_mint,
         associated_token::authority = owner,
-        payer = owner)]
+        payer = claimer)]
     pub reward_a_destination: Box<Account<'info, TokenAccount>>,
 
+    // reward b
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_b_mint.key().as_ref(),
+        ],
+        bump = bump_pot_b)]
+    pub reward_b_pot: Box<Account<'info, TokenAccount>>,
+
+    pub reward_b_mint: Box<Account<'info, Mint>>,
+
+    #[account(init_if_needed,
+        associated_token::mint = reward_b_mint,
+        associated_token::authority = owner,
+        payer = claimer)]
+    pub reward_b_destination: Box<Account<'info, TokenAccount>>,
+
     // misc
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+    //
+    // remaining accounts: the vault's StakedGem accounts to claim for (writable)
 }
 
 impl<'info> ClaimReward<'info> {
@@ -67,24 +87,108 @@ impl<'info> ClaimReward<'info> {
             },
         )
     }
+
+    fn transfer_b_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_b_pot.to_account_info(),
+                to: self.reward_b_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
+        )
+    }
 }
 
-pub fn handler(ctx: Context<ClaimReward>) -> ProgramResult {
-    // update accrued rewards before claiming
-    let vault = &mut ctx.accounts.vault;
+/// claims for each of the vault's staked gems passed in, then renews any tenures that expired.
+/// tier0 accrual that pending funds couldn't cover is added to what the vault is owed.
+/// returns the total amounts of reward a and b to be transferred out of the pots
+pub fn claim_staked_gems<'info>(
+    farm: &mut Farm,
+    vault: &mut Account<'info, Vault>,
+    staked_gems: &[AccountInfo<'info>],
+    pot_balances: (u64, u64),
+    now: u64,
+    program_id: &Pubkey,
+) -> Result<(u64, u64), ProgramError> {
+    let (pot_a_balance, pot_b_balance) = pot_balances;
+    let mut to_claim_a: u64 = 0;
+    let mut to_claim_b: u64 = 0;
+
+    for staked_gem_info in staked_gems {
+        let mut staked_gem = Account::<StakedGem>::try_from(staked_gem_info)?;
+
+        if staked_gem.vault != vault.key() {
+            return Err(ErrorCode::InvalidParameter.into());
+        }
+
+        farm.update_rewards(now, Some(&mut staked_gem))?;
+
+        // a gem that's unstaking only gets what it earned before that
+        let accrual_end = staked_gem.accrual_upper_bound(now);
+
+        // calculate claimed amounts (capped at what's left in the pot)
+        let (claimed_a, owed_a) = farm.reward_a.claim_reward(
+            &mut staked_gem.reward_a,
+            pot_a_balance.try_sub(to_claim_a)?,
+            accrual_end,
+        )?;
+        let (claimed_b, owed_b) = farm.reward_b.claim_reward(
+            &mut staked_gem.reward_b,
+            pot_b_balance.try_sub(to_claim_b)?,
+            accrual_end,
+        )?;
+
+        to_claim_a.try_add_assign(claimed_a)?;
+        to_claim_b.try_add_assign(claimed_b)?;
+        vault.reward_a_owed.try_add_assign(owed_a)?;
+        vault.reward_b_owed.try_add_assign(owed_b)?;
+
+        // everything's been claimed, so any expired tenures can roll over
+        farm.renew_tenures(vault, &mut staked_gem, now)?;
+
+        staked_gem.exit(program_id)?;
+    }
+
+    Ok((to_claim_a, to_claim_b))
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ClaimReward<'info>>) -> ProgramResult {
     let farm = &mut ctx.accounts.farm;
 
     let now = now_ts()?;
 
-    // calculate claimed amounts (capped at what's available in the pot)
-    let to_claim_a = vault.reward_a.claim_rewards(
-        ctx.accounts.reward_a_pot.amount,
+    ctx.accounts
+        .vault
+        .assert_owner_or_delegate(&ctx.accounts.claimer.key())?;
+    farm.assert_not_paused()?;
+    ctx.accounts.vault.assert_not_frozen()?;
+
+    let (mut to_claim_a, mut to_claim_b) = claim_staked_gems(
+        farm,
+        &mut ctx.accounts.vault,
+        ctx.remaining_accounts,
+        (
+            ctx.accounts.reward_a_pot.amount,
+            ctx.accounts.reward_b_pot.amount,
+        ),
         now,
-        farm.reward_a.fixed_rate.schedule.denominator,
+        ctx.program_id,
+    )?;
+
+    // then anything the vault was owed from earlier claims and withdrawals
+    let (owed_a, owed_b) = ctx.accounts.farm.pay_owed_rewards(
+        &mut ctx.accounts.vault,
+        (
+            ctx.accounts.reward_a_pot.amount.try_sub(to_claim_a)?,
+            ctx.accounts.reward_b_pot.amount.try_sub(to_claim_b)?,
+        ),
     )?;
+    to_claim_a.try_add_assign(owed_a)?;
+    to_claim_b.try_add_assign(owed_b)?;
 
     // // do the transfers
-    msg!("claiming {} rewards ", to_claim_a);
+    msg!("claiming {} A rewards and {} B rewards", to_claim_a, to_claim_b);
     if to_claim_a > 0 {
         token::transfer(
             ctx.accounts
@@ -93,6 +197,14 @@ pub fn handler(ctx: Context<ClaimReward>) -> ProgramResult {
             to_claim_a,
         )?;
     }
+    if to_claim_b > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_b_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            to_claim_b,
+        )?;
+    }
 
     Ok(())
 }
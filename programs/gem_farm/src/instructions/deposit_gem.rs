@@ -5,10 +5,31 @@ use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 use gem_common::{errors::ErrorCode, *};
 use metaplex_token_metadata::state::Metadata;
 
-use crate::state::*;
+use crate::{merkle, metadata::MetadataExtension, state::*};
+
+/// what the farmer picks for a deposit, kept in one struct rather than a long arg list.
+/// stake_in_place takes the same args
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DepositGemArgs {
+    /// 1 for an nft, any amount for fungible / semi-fungible gems. always 1 when staking in place
+    pub amount: u64,
+
+    /// the fixed rate tier to stake on, tier0 if none is passed
+    pub reward_a_tier_config: Option<TierConfig>,
+
+    pub reward_b_tier_config: Option<TierConfig>,
+
+    /// only taken from the vault's first gem, see handler
+    pub auto_renew: bool,
+
+    /// only needed if the farm has a mint merkle root
+    pub merkle_proof: Vec<[u8; 32]>,
+
+    pub merkle_rarity_points: Option<u16>,
+}
 
 #[derive(Accounts)]
-#[instruction(bump_auth: u8, bump_gem_box: u8)]
+#[instruction(bump_auth: u8, bump_gem_box: u8, bump_staked_gem: u8, bump_gdr: u8, bump_denylist: u8)]
 pub struct DepositGem<'info> {
     // farm
     #[account(mut)]
@@ -16,13 +37,13 @@ pub struct DepositGem<'info> {
 
     // vault
     // skipped vault PDA verification because requires passing in creator, which is tedious
-    // sec wise secure enough: vault has owner -> owner is signer
+    // sec wise secure enough: vault has owner -> owner or delegate is signer
     #[account(mut, has_one = farm, has_one = owner, has_one = authority)]
     pub vault: Box<Account<'info, Vault>>,
-    // currently only the vault owner can deposit
-    // add a "depositor" account, and remove Signer from vault owner to let anyone to deposit
+    pub owner: AccountInfo<'info>,
+    // the vault owner or its delegate (checked in handler), pays for and sends the gems
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub depositor: Signer<'info>,
     #[account(seeds = [vault.key().as_ref()], bump = bump_auth)]
     pub authority: AccountInfo<'info>,
 
@@ -30,26 +51,62 @@ pub struct DepositGem<'info> {
     #[account(init_if_needed, seeds = [
             b"gem_box".as_ref(),
             vault.key().as_ref(),
+            gem_mint.key().as_ref(),
         ],
         bump = bump_gem_box,
         token::mint = gem_mint,
         token::authority = authority,
-        payer = owner)]
+        payer = depositor)]
     pub gem_box: Box<Account<'info, TokenAccount>>,
 
+    // one stake record per mint per vault - depositing more of a mint that's already staked
+    // tops the existing record up, see handler
+    #[account(init_if_needed, seeds = [
+            b"staked_gem".as_ref(),
+            vault.key().as_ref(),
+            gem_mint.key().as_ref(),
+        ],
+        bump = bump_staked_gem,
+        payer = depositor,
+        space = 8 + std::mem::size_of::<StakedGem>())]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    // created by a mint's first deposit into the vault, incremented by any after that.
+    // withdrawals decrement it and close it once it's back at 0
+    #[account(init_if_needed, seeds = [
+            b"gem_deposit_receipt".as_ref(),
+            vault.key().as_ref(),
+            gem_mint.key().as_ref(),
+        ],
+        bump = bump_gdr,
+        payer = depositor,
+        space = 8 + std::mem::size_of::<GemDepositReceipt>())]
+    pub gem_deposit_receipt: Box<Account<'info, GemDepositReceipt>>,
+
     #[account(mut)]
     pub gem_source: Box<Account<'info, TokenAccount>>,
     pub gem_mint: Box<Account<'info, Mint>>,
 
+    // only has data if the mint has been denylisted, see handler
+    #[account(seeds = [
+            b"denylist".as_ref(),
+            farm.key().as_ref(),
+            gem_mint.key().as_ref(),
+        ],
+        bump = bump_denylist)]
+    pub mint_denylist_proof: AccountInfo<'info>,
+
     // misc
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
     //
     // remaining accounts could be passed, in this order:
-    // - mint_whitelist_proof
+    // - mint_whitelist_proof <- if the farm has whitelisted mints
     // - gem_metadata <- if we got to this point we can assume gem = NFT, not a fungible token
-    // - creator_whitelist_proof
+    // - creator_whitelist_proof <- for one of the gem's verified creators, if the farm has any
+    // - collection_whitelist_proof <- for the gem's verified collection, if the farm has any
+    // - update_authority_whitelist_proof <- if the farm has whitelisted update authorities
 }
 
 impl<'info> DepositGem<'info> {
@@ -59,7 +116,7 @@ impl<'info> DepositGem<'info> {
             Transfer {
                 from: self.gem_source.to_account_info(),
                 to: self.gem_box.to_account_info(),
-                authority: self.owner.to_account_info(),
+                authority: self.depositor.to_account_info(),
             },
         )
     }
@@ -72,7 +129,9 @@ fn assert_valid_metadata(
     let metadata_program = Pubkey::from_str("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").unwrap();
 
     // 1 verify the owner of the account is metaplex's metadata program
-    assert_eq!(gem_metadata.owner, &metadata_program);
+    if gem_metadata.owner != &metadata_program {
+        return Err(ErrorCode::WrongMetadata.into());
+    }
 
     // 2 verify the PDA seeds match
     let seed = &[
@@ -82,61 +141,229 @@ fn assert_valid_metadata(
     ];
 
     let (metadata_addr, _bump) = Pubkey::find_program_address(seed, &metadata_program);
-    assert_eq!(metadata_addr, gem_metadata.key());
+    if metadata_addr != gem_metadata.key() {
+        return Err(ErrorCode::WrongMetadata.into());
+    }
 
     Metadata::from_account_info(gem_metadata)
 }
 
-fn assert_whitelisted(ctx: &Context<DepositGem>) -> ProgramResult {
-    let farm = &*ctx.accounts.farm;
-    let mint = &*ctx.accounts.gem_mint;
-    let remaining_accs = &mut ctx.remaining_accounts.iter();
+fn assert_valid_whitelist_proof(
+    whitelist_proof: &AccountInfo,
+    farm: &Pubkey,
+    address_to_whitelist: &Pubkey,
+    expected_whitelist_type: WhitelistType,
+) -> ProgramResult {
+    // deserializing checks the proof is owned by this program
+    let proof = Account::<WhitelistProof>::try_from(whitelist_proof)?;
 
-    // we expect only one remaining account, which is the metadata info
-    let metadata_info = next_account_info(remaining_accs)?;
+    if proof.farm != *farm || proof.whitelisted_address != *address_to_whitelist {
+        return Err(ErrorCode::NotWhitelisted.into());
+    }
 
-    // verify metadata is legit
-    let metadata = assert_valid_metadata(metadata_info, &mint.key())?;
+    proof.contains_type(expected_whitelist_type)
+}
 
-    let whitelisted_candy_machine = farm.config.whitelisted_candy_machine.unwrap();
+fn assert_valid_update_authority(
+    update_authority_whitelist_proof: &AccountInfo,
+    farm: &Pubkey,
+    metadata: &Metadata,
+) -> ProgramResult {
+    let proof = Account::<WhitelistProof>::try_from(update_authority_whitelist_proof)?;
 
-    let creators = &metadata.data.creators.unwrap();
+    if proof.farm != *farm {
+        return Err(ErrorCode::NotWhitelisted.into());
+    }
 
-    let on_chain_candy_machine = creators.first().unwrap();
+    proof.contains_type(WhitelistType::UPDATE_AUTHORITY)?;
 
-    if whitelisted_candy_machine.key() != on_chain_candy_machine.address {
-        return Err(ErrorCode::NotWhitelisted.into());
+    // a legit proof, just not for the authority this gem was minted under
+    if proof.whitelisted_address != metadata.update_authority {
+        return Err(ErrorCode::WrongUpdateAuthority.into());
     }
 
     Ok(())
 }
 
-pub fn handler(
-    ctx: Context<DepositGem>,
-    reward_a_tier_config: Option<TierConfig>,
+fn assert_whitelisted(
+    farm: &Account<Farm>,
+    mint: &Pubkey,
+    remaining_accounts: &[AccountInfo],
 ) -> ProgramResult {
+    let remaining_accs = &mut remaining_accounts.iter();
+
+    // whitelisted mint is always the 1st optional account
+    // this is because it's applicable to both NFTs and standard fungible tokens
+    if farm.whitelisted_mints > 0 {
+        let mint_whitelist_proof_info = next_account_info(remaining_accs)?;
+
+        if assert_valid_whitelist_proof(
+            mint_whitelist_proof_info,
+            &farm.key(),
+            mint,
+            WhitelistType::MINT,
+        )
+        .is_ok()
+        {
+            return Ok(());
+        }
+    }
+
+    // the rest of the whitelists all go off the gem's metadata
+    if farm.whitelisted_creators == 0
+        && farm.whitelisted_collections == 0
+        && farm.whitelisted_update_authorities == 0
+    {
+        return Err(ErrorCode::NotWhitelisted.into());
+    }
+
+    let metadata_info = next_account_info(remaining_accs)?;
+
+    // verify metadata is legit
+    let metadata = assert_valid_metadata(metadata_info, mint)?;
+
+    // if mint verification above failed, attempt to verify based on creator
+    if farm.whitelisted_creators > 0 {
+        let creator_whitelist_proof_info = next_account_info(remaining_accs)?;
+
+        // anyone can list any address as an (unverified) creator,
+        // so only creators who actually signed off on the NFT count
+        for creator in metadata.data.creators.iter().flatten() {
+            if !creator.verified {
+                continue;
+            }
+
+            if assert_valid_whitelist_proof(
+                creator_whitelist_proof_info,
+                &farm.key(),
+                &creator.address,
+                WhitelistType::CREATOR,
+            )
+            .is_ok()
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    // then attempt to verify based on collection, which covers every drop in it
+    if farm.whitelisted_collections > 0 {
+        let collection_whitelist_proof_info = next_account_info(remaining_accs)?;
+
+        let extension = MetadataExtension::read(&metadata_info.data.borrow(), &metadata)?;
+
+        if let Some(collection) = extension.verified_collection() {
+            if assert_valid_whitelist_proof(
+                collection_whitelist_proof_info,
+                &farm.key(),
+                &collection,
+                WhitelistType::COLLECTION,
+            )
+            .is_ok()
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    // finally, attempt to verify based on the update authority
+    if farm.whitelisted_update_authorities > 0 {
+        let update_authority_whitelist_proof_info = next_account_info(remaining_accs)?;
+
+        return assert_valid_update_authority(
+            update_authority_whitelist_proof_info,
+            &farm.key(),
+            &metadata,
+        );
+    }
+
+    Err(ErrorCode::NotWhitelisted.into())
+}
+
+/// runs the gem past the farm's denylist / merkle allowlist / whitelists,
+/// returns how many rarity points each unit of the gem is worth
+pub(crate) fn assert_eligible(
+    farm: &Account<Farm>,
+    gem_mint: &Pubkey,
+    mint_denylist_proof: &AccountInfo,
+    remaining_accounts: &[AccountInfo],
+    merkle_proof: &[[u8; 32]],
+    merkle_rarity_points: Option<u16>,
+) -> Result<u64, ProgramError> {
+    // denylisting trumps any whitelist. the PDA can only have been allocated by this program,
+    // so any data there means add_to_denylist was called for this mint
+    if !mint_denylist_proof.data_is_empty() {
+        return Err(ErrorCode::MintDenylisted.into());
+    }
+
+    // a mint proven against the merkle root doesn't need any of the other whitelists
+    let merkle_allowlisted = farm.has_mint_merkle_root()
+        && merkle::verify_proof(
+            merkle_proof,
+            farm.mint_merkle_root,
+            merkle::mint_leaf(gem_mint, merkle_rarity_points),
+        );
+
+    // rarity weights are only ever taken from the merkle tree, never on the depositor's word
+    if !merkle_allowlisted && (!merkle_proof.is_empty() || merkle_rarity_points.is_some()) {
+        return Err(ErrorCode::InvalidMerkleProof.into());
+    }
+
     // if even a single whitelist exists, verify the token against it
+    if !merkle_allowlisted
+        && (farm.whitelisted_mints > 0
+            || farm.whitelisted_creators > 0
+            || farm.whitelisted_collections > 0
+            || farm.whitelisted_update_authorities > 0
+            || farm.has_mint_merkle_root())
+    {
+        assert_whitelisted(farm, gem_mint, remaining_accounts)?;
+    }
+
+    Ok(merkle_rarity_points.unwrap_or(1) as u64)
+}
+
+pub fn handler(ctx: Context<DepositGem>, args: DepositGemArgs) -> ProgramResult {
+    let DepositGemArgs {
+        amount,
+        reward_a_tier_config,
+        reward_b_tier_config,
+        auto_renew,
+        merkle_proof,
+        merkle_rarity_points,
+    } = args;
+
     let farm = &*ctx.accounts.farm;
     let vault = &*ctx.accounts.vault;
 
     let now = now_ts()?;
 
-    if vault.access_suspended()? {
-        return Err(ErrorCode::VaultAccessSuspended.into());
-    }
+    farm.assert_not_paused()?;
+    vault.assert_not_frozen()?;
+
+    vault.assert_owner_or_delegate(&ctx.accounts.depositor.key())?;
 
-    // Verify the candy machine for the gem about to be deposited is whitelisted.
-    if farm.config.whitelisted_candy_machine.is_some() {
-        assert_whitelisted(&ctx)?;
+    if amount == 0 {
+        return Err(ErrorCode::InvalidParameter.into());
     }
 
-    // validate tier_config for fixed reward types.
+    let rarity_points_per_gem = assert_eligible(
+        &ctx.accounts.farm,
+        &ctx.accounts.gem_mint.key(),
+        &ctx.accounts.mint_denylist_proof,
+        ctx.remaining_accounts,
+        &merkle_proof,
+        merkle_rarity_points,
+    )?;
+
+    // validate tier_config for fixed reward types + make sure the rewards are still running.
     // if no tier config was passed, then we use tier0
-    if matches!(farm.reward_a.reward_type, RewardType::Fixed) && reward_a_tier_config.is_some() {
-        farm.reward_a
-            .fixed_rate
-            .schedule
-            .assert_valid_tier_config(reward_a_tier_config.unwrap())?;
+    farm.reward_a.assert_valid_deposit(now, reward_a_tier_config)?;
+    farm.reward_b.assert_valid_deposit(now, reward_b_tier_config)?;
+
+    // a gem staked in place never left the owner's wallet, there's no box to top up
+    if ctx.accounts.staked_gem.frozen_in_place {
+        return Err(ErrorCode::InvalidParameter.into());
     }
 
     // do the transfer
@@ -144,22 +371,61 @@ pub fn handler(
         ctx.accounts
             .transfer_ctx()
             .with_signer(&[&vault.vault_seeds()]),
-        1, // its an nft. it's always gonna be 1.
+        amount, // 1 for an nft, any amount for fungible / semi-fungible gems
     )?;
 
     let farm = &mut ctx.accounts.farm;
     let vault = &mut ctx.accounts.vault;
-    let gem_box = &*ctx.accounts.gem_box;
+    let staked_gem = &mut ctx.accounts.staked_gem;
+
+    // a top up restarts the min staking period for the whole gem_count
+    let topping_up = staked_gem.gem_count > 0;
+    staked_gem.min_staking_ends_ts = now.try_add(farm.config.min_staking_sec)?;
+
+    // keep the receipt up to date, so the gem can be found by indexers
+    let gdr = &mut *ctx.accounts.gem_deposit_receipt;
+    gdr.vault = vault.key();
+    gdr.gem_box_address = ctx.accounts.gem_box.key();
+    gdr.gem_mint = ctx.accounts.gem_mint.key();
+    gdr.gem_count.try_add_assign(amount)?;
+
+    // the choice applies to every gem in the vault, so only the first one in gets to make it.
+    // after that it's up to set_auto_renew, rather than flipping it for gems already staked
+    if vault.gem_count == 0 {
+        vault.auto_renew = auto_renew;
+    }
 
-    // 1. calculate how much we're reserving for this deposited gem
-    // 2. record that amount on the farm reward (rewardA and rewardB)
-    // 3. record the deposit time
-    // 4. record the deposit tier the user selected
-    farm.reserve_rewards(vault, now, reward_a_tier_config)?;
+    if topping_up {
+        let rarity_points = staked_gem
+            .gem_count
+            .try_add(amount)?
+            .try_mul(rarity_points_per_gem)?;
 
-    // record the gem on vault and lock the vault
-    vault.locked = true;
-    vault.gem_mint = gem_box.mint;
+        return farm.top_up_gem(
+            vault,
+            staked_gem,
+            now,
+            amount,
+            rarity_points,
+            (reward_a_tier_config, reward_b_tier_config),
+        );
+    }
+
+    // record the gem and the box it sits in
+    staked_gem.vault = vault.key();
+    staked_gem.gem_mint = ctx.accounts.gem_mint.key();
+    staked_gem.gem_box = ctx.accounts.gem_box.key();
+    staked_gem.gem_count = amount;
+
+    let rarity_points = amount.try_mul(rarity_points_per_gem)?;
+    farm.stake_gem(
+        vault,
+        staked_gem,
+        now,
+        rarity_points,
+        reward_a_tier_config,
+        reward_b_tier_config,
+    )?;
 
     Ok(())
 }
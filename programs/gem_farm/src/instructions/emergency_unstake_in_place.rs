@@ -0,0 +1,119 @@
+use anchor_lang::{prelude::*, solana_program::program::invoke};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use gem_common::{errors::ErrorCode, *};
+
+use crate::{metadata, state::*};
+
+#[derive(Accounts)]
+#[instruction(bump_vault_auth: u8)]
+pub struct EmergencyUnstakeInPlace<'info> {
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    // vault
+    #[account(mut, has_one = farm, has_one = owner, has_one = authority)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    // owner only, same as unstake_in_place - revoking takes the token account owner's signature
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [vault.key().as_ref()], bump = bump_vault_auth)]
+    pub authority: AccountInfo<'info>,
+
+    // gem
+    #[account(mut, has_one = vault, has_one = gem_mint,
+        constraint = staked_gem.frozen_in_place,
+        constraint = staked_gem.gem_box == gem_token_account.key())]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    #[account(mut, has_one = vault, has_one = gem_mint)]
+    pub gem_deposit_receipt: Box<Account<'info, GemDepositReceipt>>,
+
+    #[account(mut)]
+    pub gem_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub gem_mint: Box<Account<'info, Mint>>,
+
+    // the mint's master edition, which is its freeze authority - verified by metaplex
+    pub gem_edition: AccountInfo<'info>,
+
+    // misc
+    #[account(address = metaplex_token_metadata::id())]
+    pub token_metadata_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> EmergencyUnstakeInPlace<'info> {
+    // anchor_spl doesn't wrap revoke
+    fn revoke(&self) -> ProgramResult {
+        invoke(
+            &spl_token::instruction::revoke(
+                &spl_token::ID,
+                &self.gem_token_account.key(),
+                &self.owner.key(),
+                &[],
+            )?,
+            &[
+                self.gem_token_account.to_account_info(),
+                self.owner.to_account_info(),
+                self.token_program.to_account_info(),
+            ],
+        )
+    }
+}
+
+/// emergency_withdraw for gems staked in place: only while the farm is paused, thaws the gem
+/// where it sits, skipping min staking, cooldown and any reward math. same as there, whatever
+/// the gem earned is forfeited and what was held for it goes back into pending funds
+pub fn handler(ctx: Context<EmergencyUnstakeInPlace>) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+    let vault = &mut ctx.accounts.vault;
+    let staked_gem = &mut ctx.accounts.staked_gem;
+
+    if !farm.paused {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    vault.assert_not_frozen()?;
+
+    // gems that are unstaking already gave up their rarity points
+    if !staked_gem.unstaking {
+        let rarity_points = staked_gem.rarity_points;
+        farm.unstake_rarity_points(vault, staked_gem, rarity_points)?;
+    }
+
+    farm.forfeit_rewards(staked_gem)?;
+    farm.remove_gem_from_vault(vault)?;
+    vault.in_place_gem_count.try_sub_assign(1)?;
+    ctx.accounts
+        .gem_deposit_receipt
+        .gem_count
+        .try_sub_assign(1)?;
+
+    // hand the gem back: thaw it, then take away the vault authority's delegation
+    let vault = &ctx.accounts.vault;
+    metadata::thaw_delegated_account(
+        &ctx.accounts.token_metadata_program,
+        &ctx.accounts.authority,
+        &ctx.accounts.gem_token_account.to_account_info(),
+        &ctx.accounts.gem_edition,
+        &ctx.accounts.gem_mint.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[&vault.vault_seeds()],
+    )?;
+    ctx.accounts.revoke()?;
+
+    let owner = &mut ctx.accounts.owner.to_account_info();
+    let staked_gem = &mut (*ctx.accounts.staked_gem).to_account_info();
+    close_account(staked_gem, owner)?;
+
+    if ctx.accounts.gem_deposit_receipt.gem_count == 0 {
+        let gdr = &mut (*ctx.accounts.gem_deposit_receipt).to_account_info();
+        close_account(gdr, owner)?;
+    }
+
+    msg!("emergency thawed gem {}", ctx.accounts.gem_mint.key());
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::*,
+    token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer},
+};
+
+use gem_common::{errors::ErrorCode, *};
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump_vault_auth: u8, bump_gem_box: u8)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    // vault
+    #[account(mut, has_one = farm, has_one = owner, has_one = authority)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    // the gem and any rent freed up go to the owner
+    #[account(mut)]
+    pub owner: AccountInfo<'info>,
+
+    // the vault owner or its delegate (checked in handler)
+    #[account(mut)] //payer
+    pub withdrawer: Signer<'info>,
+
+    #[account(seeds = [vault.key().as_ref()], bump = bump_vault_auth)]
+    pub authority: AccountInfo<'info>,
+
+    #[account(mut, seeds = [
+        b"gem_box".as_ref(),
+        vault.key().as_ref(),
+        gem_mint.key().as_ref(),
+    ],
+    bump = bump_gem_box)]
+    pub gem_box: Box<Account<'info, TokenAccount>>,
+
+    // gems staked in place have no gem box, they come out via emergency_unstake_in_place
+    #[account(mut, has_one = vault, has_one = gem_mint, has_one = gem_box)]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    #[account(mut, has_one = vault, has_one = gem_mint)]
+    pub gem_deposit_receipt: Box<Account<'info, GemDepositReceipt>>,
+
+    #[account(init_if_needed,
+        associated_token::mint = gem_mint,
+        associated_token::authority = owner,
+        payer = withdrawer)]
+    pub gem_destination: Box<Account<'info, TokenAccount>>,
+
+    pub gem_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> EmergencyWithdraw<'info> {
+    fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.gem_box.to_account_info(),
+                to: self.gem_destination.to_account_info(),
+                authority: self.authority.to_account_info(),
+            },
+        )
+    }
+
+    fn close_gem_box_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.gem_box.to_account_info(),
+                destination: self.owner.to_account_info(),
+                authority: self.authority.clone(),
+            },
+        )
+    }
+}
+
+/// only while the farm is paused: hands the whole gem back, skipping min staking, cooldown
+/// and any reward math (which might be what's broken). whatever the gem earned is forfeited,
+/// and what was held for it goes back into the farm's pending funds
+pub fn handler(ctx: Context<EmergencyWithdraw>) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+    let vault = &mut ctx.accounts.vault;
+    let staked_gem = &mut ctx.accounts.staked_gem;
+
+    if !farm.paused {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    vault.assert_owner_or_delegate(&ctx.accounts.withdrawer.key())?;
+    vault.assert_not_frozen()?;
+
+    // gems that are unstaking already gave up their rarity points
+    if !staked_gem.unstaking {
+        let rarity_points = staked_gem.rarity_points;
+        farm.unstake_rarity_points(vault, staked_gem, rarity_points)?;
+    }
+
+    farm.forfeit_rewards(staked_gem)?;
+    farm.remove_gem_from_vault(vault)?;
+
+    let amount = staked_gem.gem_count;
+    staked_gem.gem_count = 0;
+
+    let gdr = &mut ctx.accounts.gem_deposit_receipt;
+    gdr.gem_count.try_sub_assign(amount)?;
+
+    let vault = &ctx.accounts.vault;
+
+    token::transfer(
+        ctx.accounts
+            .transfer_ctx()
+            .with_signer(&[&vault.vault_seeds()]),
+        amount,
+    )?;
+    token::close_account(
+        ctx.accounts
+            .close_gem_box_ctx()
+            .with_signer(&[&vault.vault_seeds()]),
+    )?;
+
+    let owner = &mut ctx.accounts.owner.to_account_info();
+
+    let staked_gem = &mut (*ctx.accounts.staked_gem).to_account_info();
+    close_account(staked_gem, owner)?;
+
+    // the receipt only goes once the last of this mint has left the vault
+    if ctx.accounts.gem_deposit_receipt.gem_count == 0 {
+        let gdr = &mut (*ctx.accounts.gem_deposit_receipt).to_account_info();
+        close_account(gdr, owner)?;
+    }
+
+    msg!(
+        "emergency withdrew {} of gem {}",
+        amount,
+        ctx.accounts.gem_mint.key()
+    );
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use gem_common::{errors::ErrorCode, *};
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct FreezeVault<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    pub farm_manager: Signer<'info>,
+
+    // vault
+    #[account(mut, has_one = farm)]
+    pub vault: Box<Account<'info, Vault>>,
+    //
+    // remaining accounts: every one of the vault's StakedGem accounts (writable)
+}
+
+/// runs f over every one of the vault's staked gems - if any were missing, they'd keep
+/// earning while frozen (or pick up what they missed on unfreeze)
+pub(crate) fn for_each_staked_gem<'info>(
+    vault: &Account<'info, Vault>,
+    staked_gems: &[AccountInfo<'info>],
+    program_id: &Pubkey,
+    mut f: impl FnMut(&mut StakedGem) -> ProgramResult,
+) -> ProgramResult {
+    if staked_gems.len() as u64 != vault.gem_count {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    for (i, staked_gem_info) in staked_gems.iter().enumerate() {
+        // passing the same gem twice would make up for a missing one
+        if staked_gems[..i]
+            .iter()
+            .any(|other| other.key == staked_gem_info.key)
+        {
+            return Err(ErrorCode::InvalidParameter.into());
+        }
+
+        let mut staked_gem = Account::<StakedGem>::try_from(staked_gem_info)?;
+
+        if staked_gem.vault != vault.key() {
+            return Err(ErrorCode::InvalidParameter.into());
+        }
+
+        f(&mut staked_gem)?;
+
+        staked_gem.exit(program_id)?;
+    }
+
+    Ok(())
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FreezeVault<'info>>) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+    let vault = &mut ctx.accounts.vault;
+
+    if vault.frozen {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    let now = now_ts()?;
+
+    for_each_staked_gem(
+        vault,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        |staked_gem| farm.pause_gem_accrual(staked_gem, now),
+    )?;
+
+    vault.frozen = true;
+    vault.frozen_at = now;
+
+    msg!("vault {} frozen", vault.key());
+    Ok(())
+}
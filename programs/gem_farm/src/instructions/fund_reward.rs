@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use gem_common::*;
 
 use crate::state::*;
 
@@ -53,12 +54,17 @@ impl<'info> FundReward<'info> {
 
 pub fn handler(
     ctx: Context<FundReward>,
-    amount: u64
+    amount: u64,
+    duration_sec: Option<u64>,
 ) -> ProgramResult {
     // update existing rewards + record new ones
     let farm = &mut ctx.accounts.farm;
 
-    farm.fund_reward(amount)?;
+    let now = now_ts()?;
+
+    farm.assert_not_paused()?;
+
+    farm.fund_reward(now, ctx.accounts.reward_mint.key(), amount, duration_sec)?;
 
     // do the transfer
     token::transfer(
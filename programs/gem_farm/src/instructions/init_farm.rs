@@ -4,8 +4,21 @@ use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate::state::*;
 use gem_common::{errors::ErrorCode};
 
+/// how each of the farm's two rewards gets paid out, kept in one struct rather than a long arg list
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InitFarmArgs {
+    pub reward_type_a: RewardType,
+
+    pub reward_type_b: RewardType,
+
+    /// required for fixed rewards, has to be left out for variable ones
+    pub reward_a_fixed_reward_schedule: Option<FixedRateSchedule>,
+
+    pub reward_b_fixed_reward_schedule: Option<FixedRateSchedule>,
+}
+
 #[derive(Accounts)]
-#[instruction(bump_auth: u8, bump_treasury: u8, bump_pot_a: u8)]
+#[instruction(bump_auth: u8, bump_treasury: u8, bump_pot_a: u8, bump_pot_b: u8)]
 pub struct InitFarm<'info> {
     // farm
     #[account(init, payer = payer, space = 8 + std::mem::size_of::<Farm>())]
@@ -35,6 +48,19 @@ pub struct InitFarm<'info> {
     pub reward_a_pot: Box<Account<'info, TokenAccount>>,
     pub reward_a_mint: Box<Account<'info, Mint>>,
 
+    // reward b
+    #[account(init, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_b_mint.key().as_ref(),
+        ],
+        bump = bump_pot_b,
+        token::mint = reward_b_mint,
+        token::authority = farm_authority,
+        payer = payer)]
+    pub reward_b_pot: Box<Account<'info, TokenAccount>>,
+    pub reward_b_mint: Box<Account<'info, Mint>>,
+
     // misc
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -46,23 +72,40 @@ pub struct InitFarm<'info> {
 pub fn handler(
     ctx: Context<InitFarm>,
     bump_auth: u8,
-    reward_type_a: RewardType,
     // reward configurations
-    reward_a_fixed_reward_schedule: Option<FixedRateSchedule>,
+    args: InitFarmArgs,
     // farm configuration
     farm_config: FarmConfig,
 ) -> ProgramResult {
+    let InitFarmArgs {
+        reward_type_a,
+        reward_type_b,
+        reward_a_fixed_reward_schedule,
+        reward_b_fixed_reward_schedule,
+    } = args;
+
     //record new farm details
     let farm = &mut ctx.accounts.farm;
 
-    // let's block variable rewards for now
-    if matches!(reward_type_a, RewardType::Variable) {
-        return Err(ErrorCode::InvalidRewardType.into())
+    // the two rewards are tracked separately, so they need separate mints (and pots)
+    if ctx.accounts.reward_a_mint.key() == ctx.accounts.reward_b_mint.key() {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    // fixed rewards can't work without a schedule, variable ones don't use it
+    if matches!(reward_type_a, RewardType::Fixed) != reward_a_fixed_reward_schedule.is_some()
+        || matches!(reward_type_b, RewardType::Fixed) != reward_b_fixed_reward_schedule.is_some()
+    {
+        return Err(ErrorCode::InvalidRewardType.into());
     }
 
     // Make sure configurations are valid
-    if reward_a_fixed_reward_schedule.is_some() {
-        reward_a_fixed_reward_schedule.unwrap().verify_schedule_invariants();
+    if let Some(schedule) = reward_a_fixed_reward_schedule {
+        schedule.verify_schedule_invariants();
+    }
+
+    if let Some(schedule) = reward_b_fixed_reward_schedule {
+        schedule.verify_schedule_invariants();
     }
 
     farm.version = LATEST_FARM_VERSION;
@@ -77,9 +120,15 @@ pub fn handler(
     farm.reward_a.reward_pot = ctx.accounts.reward_a_pot.key();
     farm.reward_a.reward_type = reward_type_a;
 
-    if matches!(reward_type_a, RewardType::Fixed) {
-        farm.reward_a.fixed_rate = FixedRateReward::new(reward_a_fixed_reward_schedule.unwrap());
-    }
+    // variable farms still get the default schedule, so the denominator is never 0
+    farm.reward_a.fixed_rate =
+        FixedRateReward::new(reward_a_fixed_reward_schedule.unwrap_or_default());
+
+    farm.reward_b.reward_mint = ctx.accounts.reward_b_mint.key();
+    farm.reward_b.reward_pot = ctx.accounts.reward_b_pot.key();
+    farm.reward_b.reward_type = reward_type_b;
+    farm.reward_b.fixed_rate =
+        FixedRateReward::new(reward_b_fixed_reward_schedule.unwrap_or_default());
 
     msg!("new farm initialized");
     Ok(())
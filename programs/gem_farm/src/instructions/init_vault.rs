@@ -1,4 +1,3 @@
-use anchor_spl::token::{Mint};
 use anchor_lang::prelude::*;
 
 use crate::state::*;
@@ -15,7 +14,6 @@ pub struct InitVault<'info> {
             b"vault".as_ref(),
             farm.key().as_ref(),
             owner.key().as_ref(),
-            gem_mint.key().as_ref(),
         ],
         bump = bump,
         payer = payer,
@@ -25,8 +23,6 @@ pub struct InitVault<'info> {
     // The designated owner of this vault
     pub owner: Signer<'info>,
 
-    pub gem_mint: Box<Account<'info, Mint>>,
-
     // misc
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -46,6 +42,7 @@ pub fn handler(ctx: Context<InitVault>) -> ProgramResult {
     // record vault's state
     vault.farm = farm.key();
     vault.owner = ctx.accounts.owner.key();
+    vault.creator = ctx.accounts.owner.key();
     vault.authority = authority;
     vault.authority_seed = vault_address;
     vault.authority_bump_seed = [bump];
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use gem_common::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct LockReward<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    pub farm_manager: Signer<'info>,
+
+    // reward
+    pub reward_mint: Box<Account<'info, Mint>>,
+}
+
+pub fn handler(ctx: Context<LockReward>) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+
+    let now = now_ts()?;
+
+    farm.lock_reward_by_mint(now, ctx.accounts.reward_mint.key())?;
+
+    msg!("locked reward {}", ctx.accounts.reward_mint.key());
+    Ok(())
+}
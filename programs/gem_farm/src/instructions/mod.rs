@@ -8,6 +8,26 @@ pub mod init_vault;
 pub mod fund_reward;
 pub mod claim_rewards;
 pub mod withdraw_gem;
+pub mod cancel_reward;
+pub mod lock_reward;
+pub mod upgrade_tier;
+pub mod renew_tenure;
+pub mod set_auto_renew;
+pub mod set_vault_delegate;
+pub mod propose_vault_owner;
+pub mod accept_vault_owner;
+pub mod freeze_vault;
+pub mod unfreeze_vault;
+pub mod set_farm_paused;
+pub mod emergency_withdraw;
+pub mod begin_unstake;
+pub mod add_to_whitelist;
+pub mod remove_from_whitelist;
+pub mod add_to_denylist;
+pub mod remove_from_denylist;
+pub mod stake_in_place;
+pub mod unstake_in_place;
+pub mod emergency_unstake_in_place;
 
 pub use authorize_funder::*;
 pub use deauthorize_funder::*;
@@ -19,6 +39,26 @@ pub use init_vault::*;
 pub use fund_reward::*;
 pub use claim_rewards::*;
 pub use withdraw_gem::*;
+pub use cancel_reward::*;
+pub use lock_reward::*;
+pub use upgrade_tier::*;
+pub use renew_tenure::*;
+pub use set_auto_renew::*;
+pub use set_vault_delegate::*;
+pub use propose_vault_owner::*;
+pub use accept_vault_owner::*;
+pub use freeze_vault::*;
+pub use unfreeze_vault::*;
+pub use set_farm_paused::*;
+pub use emergency_withdraw::*;
+pub use begin_unstake::*;
+pub use add_to_whitelist::*;
+pub use remove_from_whitelist::*;
+pub use add_to_denylist::*;
+pub use remove_from_denylist::*;
+pub use stake_in_place::*;
+pub use unstake_in_place::*;
+pub use emergency_unstake_in_place::*;
 
 // have to duplicate or this won't show up in IDL
 use anchor_lang::prelude::*;
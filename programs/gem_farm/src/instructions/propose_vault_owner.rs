@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ProposeVaultOwner<'info> {
+    // vault
+    #[account(mut, has_one = owner)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub owner: Signer<'info>,
+}
+
+/// nothing changes until the new owner accepts, so a typo can't lock the vault away.
+/// pass Pubkey::default() to cancel a pending transfer.
+/// the vault keeps its address (seeded by its creator), so once it's been handed over
+/// the creator can't init another vault in this farm
+pub fn handler(ctx: Context<ProposeVaultOwner>, new_owner: Pubkey) -> ProgramResult {
+    let vault = &mut ctx.accounts.vault;
+
+    // cancelling is always fine
+    if new_owner != Pubkey::default() {
+        vault.assert_no_gems_in_place()?;
+    }
+
+    vault.pending_owner = new_owner;
+
+    msg!("{} proposed as owner of vault {}", new_owner, vault.key());
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use gem_common::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct RemoveFromDenylist<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(mut)]
+    pub farm_manager: Signer<'info>,
+
+    // denylist
+    pub mint_to_remove: AccountInfo<'info>,
+    #[account(mut, has_one = farm,
+        constraint = denylist_proof.denylisted_mint == mint_to_remove.key(),
+        seeds = [
+            b"denylist".as_ref(),
+            farm.key().as_ref(),
+            mint_to_remove.key().as_ref(),
+        ],
+        bump = bump)]
+    denylist_proof: Box<Account<'info, DenylistProof>>,
+}
+
+pub fn handler(ctx: Context<RemoveFromDenylist>) -> ProgramResult {
+    // close denylist proof
+    close_account(
+        &mut ctx.accounts.denylist_proof.to_account_info(),
+        &mut ctx.accounts.farm_manager.to_account_info(),
+    )?;
+
+    msg!(
+        "{} removed from denylist",
+        ctx.accounts.mint_to_remove.key()
+    );
+    Ok(())
+}
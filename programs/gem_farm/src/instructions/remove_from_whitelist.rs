@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use gem_common::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct RemoveFromWhitelist<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(mut)]
+    pub farm_manager: Signer<'info>,
+
+    // whitelist
+    pub address_to_remove: AccountInfo<'info>,
+    #[account(mut, has_one = farm,
+        constraint = whitelist_proof.whitelisted_address == address_to_remove.key(),
+        seeds = [
+            b"whitelist".as_ref(),
+            farm.key().as_ref(),
+            address_to_remove.key().as_ref(),
+        ],
+        bump = bump)]
+    whitelist_proof: Box<Account<'info, WhitelistProof>>,
+}
+
+pub fn handler(ctx: Context<RemoveFromWhitelist>) -> ProgramResult {
+    // update farm
+    let whitelist_type = ctx.accounts.whitelist_proof.read_type()?;
+    ctx.accounts.farm.remove_whitelist_counts(whitelist_type)?;
+
+    // close whitelist proof
+    close_account(
+        &mut ctx.accounts.whitelist_proof.to_account_info(),
+        &mut ctx.accounts.farm_manager.to_account_info(),
+    )?;
+
+    msg!(
+        "{} removed from whitelist",
+        ctx.accounts.address_to_remove.key()
+    );
+    Ok(())
+}
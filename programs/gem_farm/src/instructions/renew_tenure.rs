@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use gem_common::{errors::ErrorCode, *};
+
+use crate::{instructions::claim_staked_gems, state::*};
+
+#[derive(Accounts)]
+#[instruction(bump_auth: u8, bump_vault: u8, bump_pot_a: u8, bump_pot_b: u8)]
+pub struct RenewTenure<'info> {
+    // farm
+    #[account(mut, has_one = farm_authority)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    #[account(seeds = [farm.key().as_ref()], bump = bump_auth)]
+    pub farm_authority: AccountInfo<'info>,
+
+    // vault
+    #[account(mut, has_one = farm, has_one = owner, seeds = [
+            b"vault".as_ref(),
+            farm.key().as_ref(),
+            vault.creator.as_ref(),
+        ],
+        bump = bump_vault)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    // rewards still go to the owner, they just don't have to be around for it
+    pub owner: AccountInfo<'info>,
+
+    // renewals are permissionless, anyone can crank them
+    #[account(mut)] //payer
+    pub cranker: Signer<'info>,
+
+    // reward a
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_a_mint.key().as_ref(),
+        ],
+        bump = bump_pot_a)]
+    pub reward_a_pot: Box<Account<'info, TokenAccount>>,
+
+    pub reward_a_mint: Box<Account<'info, Mint>>,
+
+    #[account(init_if_needed,
+        associated_token::mint = reward_a_mint,
+        associated_token::authority = owner,
+        payer = cranker)]
+    pub reward_a_destination: Box<Account<'info, TokenAccount>>,
+
+    // reward b
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_b_mint.key().as_ref(),
+        ],
+        bump = bump_pot_b)]
+    pub reward_b_pot: Box<Account<'info, TokenAccount>>,
+
+    pub reward_b_mint: Box<Account<'info, Mint>>,
+
+    #[account(init_if_needed,
+        associated_token::mint = reward_b_mint,
+        associated_token::authority = owner,
+        payer = cranker)]
+    pub reward_b_destination: Box<Account<'info, TokenAccount>>,
+
+    // misc
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    //
+    // remaining accounts: the vault's StakedGem accounts to renew (writable)
+}
+
+impl<'info> RenewTenure<'info> {
+    fn transfer_a_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_a_pot.to_account_info(),
+                to: self.reward_a_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
+        )
+    }
+
+    fn transfer_b_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_b_pot.to_account_info(),
+                to: self.reward_b_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RenewTenure<'info>>) -> ProgramResult {
+    // only vaults that opted in can be renewed on their owner's behalf
+    if !ctx.accounts.vault.auto_renew {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    ctx.accounts.farm.assert_not_paused()?;
+    ctx.accounts.vault.assert_not_frozen()?;
+
+    let farm = &mut ctx.accounts.farm;
+
+    let now = now_ts()?;
+
+    // renewing starts a new tenure, so whatever was earned on the old one gets paid out first.
+    // gems that are unstaking are left as they are
+    let (to_claim_a, to_claim_b) = claim_staked_gems(
+        farm,
+        &mut ctx.accounts.vault,
+        ctx.remaining_accounts,
+        (
+            ctx.accounts.reward_a_pot.amount,
+            ctx.accounts.reward_b_pot.amount,
+        ),
+        now,
+        ctx.program_id,
+    )?;
+
+    if to_claim_a > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_a_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            to_claim_a,
+        )?;
+    }
+    if to_claim_b > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_b_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            to_claim_b,
+        )?;
+    }
+
+    msg!("renewed vault {}", ctx.accounts.vault.key());
+    Ok(())
+}
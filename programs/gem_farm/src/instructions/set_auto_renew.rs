@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetAutoRenew<'info> {
+    // vault
+    #[account(mut, has_one = owner)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetAutoRenew>, auto_renew: bool) -> ProgramResult {
+    let vault = &mut ctx.accounts.vault;
+
+    vault.auto_renew = auto_renew;
+
+    msg!("auto renew set to {} for vault {}", auto_renew, vault.key());
+    Ok(())
+}
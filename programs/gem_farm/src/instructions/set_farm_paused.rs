@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetFarmPaused<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    pub farm_manager: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetFarmPaused>, paused: bool) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+
+    farm.paused = paused;
+
+    msg!("paused set to {} for farm {}", paused, farm.key());
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetVaultDelegate<'info> {
+    // vault
+    #[account(mut, has_one = owner)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub owner: Signer<'info>,
+}
+
+/// pass Pubkey::default() to remove the delegate
+pub fn handler(ctx: Context<SetVaultDelegate>, delegate: Pubkey) -> ProgramResult {
+    let vault = &mut ctx.accounts.vault;
+
+    vault.delegate = delegate;
+
+    msg!("delegate set to {} for vault {}", delegate, vault.key());
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Approve, Mint, Token, TokenAccount};
+use gem_common::{errors::ErrorCode, *};
+
+use crate::{
+    instructions::deposit_gem::{assert_eligible, DepositGemArgs},
+    metadata,
+    state::*,
+};
+
+#[derive(Accounts)]
+#[instruction(bump_auth: u8, bump_staked_gem: u8, bump_gdr: u8, bump_denylist: u8)]
+pub struct StakeInPlace<'info> {
+    // farm
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    // vault
+    #[account(mut, has_one = farm, has_one = owner, has_one = authority)]
+    pub vault: Box<Account<'info, Vault>>,
+    // owner only, unlike deposit_gem the vault's delegate can't stake in place:
+    // the gem sits in the owner's own token account, so only they can approve the vault authority
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(seeds = [vault.key().as_ref()], bump = bump_auth)]
+    pub authority: AccountInfo<'info>,
+
+    // gem
+    // stays in the owner's wallet, the vault authority only gets to freeze it
+    #[account(mut,
+        constraint = gem_token_account.owner == owner.key(),
+        constraint = gem_token_account.mint == gem_mint.key())]
+    pub gem_token_account: Box<Account<'info, TokenAccount>>,
+
+    // same seeds as deposit_gem, so a gem can't be staked both ways in the same vault
+    #[account(init, seeds = [
+            b"staked_gem".as_ref(),
+            vault.key().as_ref(),
+            gem_mint.key().as_ref(),
+        ],
+        bump = bump_staked_gem,
+        payer = owner,
+        space = 8 + std::mem::size_of::<StakedGem>())]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    // same receipt as deposit_gem, so indexers find in-place gems too.
+    // it points at the owner's token account, there being no gem box
+    #[account(init_if_needed, seeds = [
+            b"gem_deposit_receipt".as_ref(),
+            vault.key().as_ref(),
+            gem_mint.key().as_ref(),
+        ],
+        bump = bump_gdr,
+        payer = owner,
+        space = 8 + std::mem::size_of::<GemDepositReceipt>())]
+    pub gem_deposit_receipt: Box<Account<'info, GemDepositReceipt>>,
+
+    pub gem_mint: Box<Account<'info, Mint>>,
+
+    // the mint's master edition, which is its freeze authority - verified by metaplex
+    pub gem_edition: AccountInfo<'info>,
+
+    // only has data if the mint has been denylisted, see deposit_gem
+    #[account(seeds = [
+            b"denylist".as_ref(),
+            farm.key().as_ref(),
+            gem_mint.key().as_ref(),
+        ],
+        bump = bump_denylist)]
+    pub mint_denylist_proof: AccountInfo<'info>,
+
+    // misc
+    #[account(address = metaplex_token_metadata::id())]
+    pub token_metadata_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    //
+    // remaining accounts - same as deposit_gem
+}
+
+impl<'info> StakeInPlace<'info> {
+    fn approve_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Approve<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Approve {
+                to: self.gem_token_account.to_account_info(),
+                delegate: self.authority.clone(),
+                authority: self.owner.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<StakeInPlace>, args: DepositGemArgs) -> ProgramResult {
+    let DepositGemArgs {
+        amount,
+        reward_a_tier_config,
+        reward_b_tier_config,
+        auto_renew,
+        merkle_proof,
+        merkle_rarity_points,
+    } = args;
+
+    let farm = &*ctx.accounts.farm;
+
+    let now = now_ts()?;
+
+    farm.assert_not_paused()?;
+    ctx.accounts.vault.assert_not_frozen()?;
+
+    // only NFTs have an edition to freeze them with, so there's always exactly one
+    if amount != 1 || ctx.accounts.gem_token_account.amount != 1 {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    let rarity_points = assert_eligible(
+        &ctx.accounts.farm,
+        &ctx.accounts.gem_mint.key(),
+        &ctx.accounts.mint_denylist_proof,
+        ctx.remaining_accounts,
+        &merkle_proof,
+        merkle_rarity_points,
+    )?;
+
+    // validate tier_config for fixed reward types + make sure the rewards are still running.
+    // if no tier config was passed, then we use tier0
+    farm.reward_a
+        .assert_valid_deposit(now, reward_a_tier_config)?;
+    farm.reward_b
+        .assert_valid_deposit(now, reward_b_tier_config)?;
+
+    // make the vault authority the delegate, then have it freeze the gem where it sits
+    token::approve(ctx.accounts.approve_ctx(), 1)?;
+
+    let vault = &*ctx.accounts.vault;
+    metadata::freeze_delegated_account(
+        &ctx.accounts.token_metadata_program,
+        &ctx.accounts.authority,
+        &ctx.accounts.gem_token_account.to_account_info(),
+        &ctx.accounts.gem_edition,
+        &ctx.accounts.gem_mint.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[&vault.vault_seeds()],
+    )?;
+
+    let farm = &mut ctx.accounts.farm;
+    let vault = &mut ctx.accounts.vault;
+    let staked_gem = &mut ctx.accounts.staked_gem;
+
+    // record the gem and the (owner's) token account it sits in
+    staked_gem.vault = vault.key();
+    staked_gem.gem_mint = ctx.accounts.gem_mint.key();
+    staked_gem.gem_box = ctx.accounts.gem_token_account.key();
+    staked_gem.frozen_in_place = true;
+    staked_gem.gem_count = 1;
+    staked_gem.min_staking_ends_ts = now.try_add(farm.config.min_staking_sec)?;
+    vault.in_place_gem_count.try_add_assign(1)?;
+
+    let gdr = &mut *ctx.accounts.gem_deposit_receipt;
+    gdr.vault = vault.key();
+    gdr.gem_box_address = ctx.accounts.gem_token_account.key();
+    gdr.gem_mint = ctx.accounts.gem_mint.key();
+    gdr.gem_count.try_add_assign(1)?;
+
+    // the choice applies to every gem in the vault, so only the first one in gets to make it.
+    // after that it's up to set_auto_renew, rather than flipping it for gems already staked
+    if vault.gem_count == 0 {
+        vault.auto_renew = auto_renew;
+    }
+
+    farm.stake_gem(
+        vault,
+        staked_gem,
+        now,
+        rarity_points,
+        reward_a_tier_config,
+        reward_b_tier_config,
+    )?;
+
+    msg!("gem {} frozen in place", staked_gem.gem_mint);
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use gem_common::{errors::ErrorCode, *};
+
+use crate::{instructions::freeze_vault::for_each_staked_gem, state::*};
+
+#[derive(Accounts)]
+pub struct UnfreezeVault<'info> {
+    // farm
+    #[account(mut, has_one = farm_manager)]
+    pub farm: Box<Account<'info, Farm>>,
+    pub farm_manager: Signer<'info>,
+
+    // vault
+    #[account(mut, has_one = farm)]
+    pub vault: Box<Account<'info, Vault>>,
+    //
+    // remaining accounts: every one of the vault's StakedGem accounts (writable)
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UnfreezeVault<'info>>) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+    let vault = &mut ctx.accounts.vault;
+
+    if !vault.frozen {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    let now = now_ts()?;
+    let frozen_at = vault.frozen_at;
+
+    for_each_staked_gem(
+        vault,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        |staked_gem| farm.resume_gem_accrual(staked_gem, frozen_at, now),
+    )?;
+
+    vault.frozen = false;
+    vault.frozen_at = 0;
+
+    msg!(
+        "vault {} unfrozen after {}s",
+        vault.key(),
+        now.try_sub(frozen_at)?
+    );
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use anchor_lang::{prelude::*, solana_program::program::invoke};
+use anchor_spl::{
+    associated_token::*,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use gem_common::*;
+
+use crate::{metadata, state::*};
+
+#[derive(Accounts)]
+#[instruction(bump_farm_auth: u8, bump_vault_auth: u8, bump_pot_a: u8, bump_pot_b: u8)]
+pub struct UnstakeInPlace<'info> {
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    #[account(seeds = [farm.key().as_ref()], bump = bump_farm_auth)]
+    pub farm_authority: AccountInfo<'info>,
+
+    // vault
+    #[account(mut, has_one = farm, has_one = owner, has_one = authority)]
+    pub vault: Box<Account<'info, Vault>>,
+
+    // owner only, unlike withdraw_gem the vault's delegate can't unstake in place:
+    // revoking the vault authority's delegation takes the token account owner's signature
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(seeds = [vault.key().as_ref()], bump = bump_vault_auth)]
+    pub authority: AccountInfo<'info>,
+
+    // gem
+    #[account(mut, has_one = vault, has_one = gem_mint,
+        constraint = staked_gem.frozen_in_place,
+        constraint = staked_gem.gem_box == gem_token_account.key())]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    #[account(mut, has_one = vault, has_one = gem_mint)]
+    pub gem_deposit_receipt: Box<Account<'info, GemDepositReceipt>>,
+
+    #[account(mut)]
+    pub gem_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub gem_mint: Box<Account<'info, Mint>>,
+
+    // the mint's master edition, which is its freeze authority - verified by metaplex
+    pub gem_edition: AccountInfo<'info>,
+
+    // for paying out pending rewards
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_a_mint.key().as_ref(),
+        ],
+        bump = bump_pot_a)]
+    pub reward_a_pot: Box<Account<'info, TokenAccount>>,
+
+    pub reward_a_mint: Box<Account<'info, Mint>>,
+
+    #[account(init_if_needed,
+        associated_token::mint = reward_a_mint,
+        associated_token::authority = owner,
+        payer = owner)]
+    pub reward_a_destination: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_b_mint.key().as_ref(),
+        ],
+        bump = bump_pot_b)]
+    pub reward_b_pot: Box<Account<'info, TokenAccount>>,
+
+    pub reward_b_mint: Box<Account<'info, Mint>>,
+
+    #[account(init_if_needed,
+        associated_token::mint = reward_b_mint,
+        associated_token::authority = owner,
+        payer = owner)]
+    pub reward_b_destination: Box<Account<'info, TokenAccount>>,
+
+    // misc
+    #[account(address = metaplex_token_metadata::id())]
+    pub token_metadata_program: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    pub system_program: Program<'info, System>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> UnstakeInPlace<'info> {
+    fn transfer_a_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_a_pot.to_account_info(),
+                to: self.reward_a_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
+        )
+    }
+
+    fn transfer_b_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_b_pot.to_account_info(),
+                to: self.reward_b_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
+        )
+    }
+
+    // anchor_spl doesn't wrap revoke
+    fn revoke(&self) -> ProgramResult {
+        invoke(
+            &spl_token::instruction::revoke(
+                &spl_token::ID,
+                &self.gem_token_account.key(),
+                &self.owner.key(),
+                &[],
+            )?,
+            &[
+                self.gem_token_account.to_account_info(),
+                self.owner.to_account_info(),
+                self.token_program.to_account_info(),
+            ],
+        )
+    }
+}
+
+pub fn handler(ctx: Context<UnstakeInPlace>) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+    let vault = &mut ctx.accounts.vault;
+    let staked_gem = &mut ctx.accounts.staked_gem;
+
+    let now = now_ts()?;
+
+    vault.assert_not_frozen()?;
+
+    // no payouts while paused - gems come out through emergency_unstake_in_place instead
+    farm.assert_not_paused()?;
+
+    // same rules as withdraw_gem - begin_unstake + the cooldown first
+    staked_gem.assert_cooldown_passed(now)?;
+
+    farm.remove_gem_from_vault(vault)?;
+    vault.in_place_gem_count.try_sub_assign(1)?;
+    ctx.accounts
+        .gem_deposit_receipt
+        .gem_count
+        .try_sub_assign(1)?;
+
+    // nothing's staked anymore, this only settles whatever accrued before unstaking began
+    farm.update_rewards(now, Some(staked_gem))?;
+
+    // calculate claimed amounts, earned up to when unstaking began.
+    // same as withdraw_gem, short pots don't keep the gem frozen
+    let (to_claim_a, to_claim_b) = farm.claim_rewards_or_owe(
+        vault,
+        staked_gem,
+        (
+            ctx.accounts.reward_a_pot.amount,
+            ctx.accounts.reward_b_pot.amount,
+        ),
+        now,
+    )?;
+
+    // transfer remaining rewards if any
+    if to_claim_a > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_a_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            to_claim_a,
+        )?;
+    }
+    if to_claim_b > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_b_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            to_claim_b,
+        )?;
+    }
+
+    // hand the gem back: thaw it, then take away the vault authority's delegation
+    let vault = &ctx.accounts.vault;
+    metadata::thaw_delegated_account(
+        &ctx.accounts.token_metadata_program,
+        &ctx.accounts.authority,
+        &ctx.accounts.gem_token_account.to_account_info(),
+        &ctx.accounts.gem_edition,
+        &ctx.accounts.gem_mint.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        &[&vault.vault_seeds()],
+    )?;
+    ctx.accounts.revoke()?;
+
+    let owner = &mut ctx.accounts.owner.to_account_info();
+    let staked_gem = &mut (*ctx.accounts.staked_gem).to_account_info();
+    close_account(staked_gem, owner)?;
+
+    if ctx.accounts.gem_deposit_receipt.gem_count == 0 {
+        let gdr = &mut (*ctx.accounts.gem_deposit_receipt).to_account_info();
+        close_account(gdr, owner)?;
+    }
+
+    msg!("gem {} thawed", ctx.accounts.gem_mint.key());
+    Ok(())
+}
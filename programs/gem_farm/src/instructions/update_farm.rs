@@ -14,6 +14,7 @@ pub fn handler(
     ctx: Context<UpdateFarm>,
     config: Option<FarmConfig>,
     manager: Option<Pubkey>,
+    mint_merkle_root: Option<[u8; 32]>,
 ) -> ProgramResult {
     let farm = &mut ctx.accounts.farm;
 
@@ -25,6 +26,11 @@ pub fn handler(
         farm.farm_manager = manager;
     }
 
+    // an all-zero root turns the merkle allowlist off
+    if let Some(mint_merkle_root) = mint_merkle_root {
+        farm.mint_merkle_root = mint_merkle_root;
+    }
+
     msg!("updated farm");
     Ok(())
 }
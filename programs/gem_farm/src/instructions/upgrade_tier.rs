@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use gem_common::{errors::ErrorCode, *};
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(bump_auth: u8, bump_pot: u8)]
+pub struct UpgradeTier<'info> {
+    // farm
+    #[account(mut, has_one = farm_authority)]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(seeds = [farm.key().as_ref()], bump = bump_auth)]
+    pub farm_authority: AccountInfo<'info>,
+
+    // vault
+    #[account(mut, has_one = farm, has_one = owner)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub owner: AccountInfo<'info>,
+    // the vault owner or its delegate (checked in handler), pays for the reward ATA if needed
+    #[account(mut)]
+    pub upgrader: Signer<'info>,
+
+    // the gem whose tier is being upgraded
+    #[account(mut, has_one = vault)]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    // reward being upgraded, rewards earned so far get paid out of it
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_mint.key().as_ref(),
+        ],
+        bump = bump_pot)]
+    pub reward_pot: Box<Account<'info, TokenAccount>>,
+    pub reward_mint: Box<Account<'info, Mint>>,
+    #[account(init_if_needed,
+        associated_token::mint = reward_mint,
+        associated_token::authority = owner,
+        payer = upgrader)]
+    pub reward_destination: Box<Account<'info, TokenAccount>>,
+
+    // misc
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> UpgradeTier<'info> {
+    fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_pot.to_account_info(),
+                to: self.reward_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<UpgradeTier>, tier_config: TierConfig) -> ProgramResult {
+    let farm = &mut ctx.accounts.farm;
+    let staked_gem = &mut ctx.accounts.staked_gem;
+
+    farm.assert_not_paused()?;
+    ctx.accounts.vault.assert_not_frozen()?;
+
+    ctx.accounts
+        .vault
+        .assert_owner_or_delegate(&ctx.accounts.upgrader.key())?;
+
+    // gems that are unstaking no longer have a tier to upgrade
+    if staked_gem.unstaking {
+        return Err(ErrorCode::InvalidParameter.into());
+    }
+
+    let now = now_ts()?;
+
+    let reward_mint = ctx.accounts.reward_mint.key();
+    let (farm_reward, vault_reward) = farm.match_rewards_by_mint(staked_gem, reward_mint)?;
+
+    let (to_claim, owed) = farm_reward.upgrade_tier(
+        vault_reward,
+        now,
+        tier_config,
+        ctx.accounts.reward_pot.amount,
+    )?;
+
+    // tier0 accrual pending funds couldn't cover, paid out by claim_rewards once they can
+    let vault = &mut ctx.accounts.vault;
+    if reward_mint == farm.reward_a.reward_mint {
+        vault.reward_a_owed.try_add_assign(owed)?;
+    } else {
+        vault.reward_b_owed.try_add_assign(owed)?;
+    }
+
+    if to_claim > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            to_claim,
+        )?;
+    }
+
+    msg!(
+        "gem {} moved to a {}s tenure",
+        ctx.accounts.staked_gem.gem_mint,
+        tier_config.required_tenure
+    );
+    Ok(())
+}
@@ -1,7 +1,4 @@
-use anchor_lang::{
-    prelude::*,
-    solana_program::{program::invoke, system_instruction},
-};
+use anchor_lang::prelude::*;
 
 use anchor_spl::{
     associated_token::*,
@@ -13,7 +10,7 @@ use gem_common::{errors::ErrorCode, *};
 use crate::state::*;
 
 #[derive(Accounts)]
-#[instruction(bump_farm_auth: u8, bump_treasury: u8, bump_vault_auth: u8, bump_gem_box: u8, bump_pot_a: u8)]
+#[instruction(bump_farm_auth: u8, bump_vault_auth: u8, bump_gem_box: u8, bump_pot_a: u8, bump_pot_b: u8)]
 pub struct WithdrawGem<'info> {
     #[account(mut)]
     pub farm: Box<Account<'info, Farm>>,
@@ -21,17 +18,17 @@ pub struct WithdrawGem<'info> {
     #[account(seeds = [farm.key().as_ref()], bump = bump_farm_auth)]
     pub farm_authority: AccountInfo<'info>,
 
-    #[account(mut, seeds = [b"treasury".as_ref(), farm.key().as_ref()], bump = bump_treasury)]
-    pub farm_treasury: AccountInfo<'info>,
-
     // vault
     #[account(mut, has_one = farm, has_one = owner, has_one = authority)]
     pub vault: Box<Account<'info, Vault>>,
 
-    // currently only the vault owner can deposit
-    // add a "depositor" account, and remove Signer from vault owner to let anyone to deposit
+    // gems, rewards and any rent freed up all go to the owner
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub owner: AccountInfo<'info>,
+
+    // the vault owner or its delegate (checked in handler)
+    #[account(mut)] //payer
+    pub withdrawer: Signer<'info>,
 
     #[account(seeds = [vault.key().as_ref()], bump = bump_vault_auth)]
     pub authority: AccountInfo<'info>,
@@ -39,14 +36,22 @@ pub struct WithdrawGem<'info> {
     #[account(mut, seeds = [
         b"gem_box".as_ref(),
         vault.key().as_ref(),
+        gem_mint.key().as_ref(),
     ],
     bump = bump_gem_box)]
     pub gem_box: Box<Account<'info, TokenAccount>>,
 
+    // only the gem being withdrawn, the rest of the vault stays staked
+    #[account(mut, has_one = vault, has_one = gem_mint, has_one = gem_box)]
+    pub staked_gem: Box<Account<'info, StakedGem>>,
+
+    #[account(mut, has_one = vault, has_one = gem_mint)]
+    pub gem_deposit_receipt: Box<Account<'info, GemDepositReceipt>>,
+
     #[account(init_if_needed,
         associated_token::mint = gem_mint,
         associated_token::authority = owner,
-        payer = owner)]
+        payer = withdrawer)]
     pub gem_destination: Box<Account<'info, TokenAccount>>,
 
     // for paying out pending rewards
@@ -63,9 +68,25 @@ pub struct WithdrawGem<'info> {
     #[account(init_if_needed,
         associated_token::mint = reward_a_mint,
         associated_token::authority = owner,
-        payer = owner)]
+        payer = withdrawer)]
     pub reward_a_destination: Box<Account<'info, TokenAccount>>,
 
+    #[account(mut, seeds = [
+            b"reward_pot".as_ref(),
+            farm.key().as_ref(),
+            reward_b_mint.key().as_ref(),
+        ],
+        bump = bump_pot_b)]
+    pub reward_b_pot: Box<Account<'info, TokenAccount>>,
+
+    pub reward_b_mint: Box<Account<'info, Mint>>,
+
+    #[account(init_if_needed,
+        associated_token::mint = reward_b_mint,
+        associated_token::authority = owner,
+        payer = withdrawer)]
+    pub reward_b_destination: Box<Account<'info, TokenAccount>>,
+
     pub gem_mint: Box<Account<'info, Mint>>,
 
     pub token_program: Program<'info, Token>,
@@ -111,52 +132,64 @@ impl<'info> WithdrawGem<'info> {
         )
     }
 
-    fn pay_treasury(&self, lamports: u64) -> ProgramResult {
-        invoke(
-            &system_instruction::transfer(self.owner.key, self.farm_treasury.key, lamports),
-            &[
-                self.owner.to_account_info(),
-                self.farm_treasury.clone(),
-                self.system_program.to_account_info(),
-            ],
+    fn transfer_b_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_b_pot.to_account_info(),
+                to: self.reward_b_destination.to_account_info(),
+                authority: self.farm_authority.to_account_info(),
+            },
         )
     }
 }
 
-pub fn handler(ctx: Context<WithdrawGem>) -> ProgramResult {
+pub fn handler(ctx: Context<WithdrawGem>, amount: u64) -> ProgramResult {
     let farm = &mut ctx.accounts.farm;
     let vault = &mut ctx.accounts.vault;
+    let staked_gem = &mut ctx.accounts.staked_gem;
 
-    // decrease farm vault count upfront
-    farm.vault_count.try_sub_assign(1)?;
-
-    // calculate pending rewards on vault and transfer
     let now = now_ts()?;
 
-    if vault.attempting_to_break_bank(now)? {
-        // if farmer is breaking bank, it means some reserved rewards should be unreserved
-        farm.unreserve_rewards(vault, now)?;
+    vault.assert_owner_or_delegate(&ctx.accounts.withdrawer.key())?;
+    vault.assert_not_frozen()?;
 
-        // if there is a paper hands tax, charge the user
-        if farm.config.paper_hands_tax_lamp > 0 {
-            let farm = &*ctx.accounts.farm;
+    // no payouts while paused - gems come out through emergency_withdraw instead
+    farm.assert_not_paused()?;
 
-            ctx.accounts
-                .pay_treasury(farm.config.paper_hands_tax_lamp)?;
-        } else {
-            return Err(ErrorCode::TooEarlyToWithdraw.into());
-        }
+    // the gem has to have gone through begin_unstake + the cooldown first
+    staked_gem.assert_cooldown_passed(now)?;
+
+    // fungible / semi-fungible gems can come out in several goes. whatever's left stopped
+    // earning at begin_unstake, depositing more of the mint stakes all of it again
+    if amount == 0 || amount > staked_gem.gem_count {
+        return Err(ErrorCode::InvalidParameter.into());
     }
 
-    let farm = &mut ctx.accounts.farm;
-    let vault = &mut ctx.accounts.vault;
+    staked_gem.gem_count.try_sub_assign(amount)?;
 
+    let gdr = &mut ctx.accounts.gem_deposit_receipt;
+    gdr.gem_count.try_sub_assign(amount)?;
+
+    // the gem only leaves the vault once all of it has been withdrawn
+    if staked_gem.gem_count == 0 {
+        farm.remove_gem_from_vault(vault)?;
+    }
 
-    // calculate claimed amounts (capped at what's available in the pot)
-    let to_claim_a = vault.reward_a.claim_rewards(
-        ctx.accounts.reward_a_pot.amount,
+    // nothing's staked anymore, this only settles whatever accrued before unstaking began
+    farm.update_rewards(now, Some(staked_gem))?;
+
+    // calculate claimed amounts, earned up to when unstaking began.
+    // the first withdrawal pays out everything, any later ones have nothing left to claim.
+    // if the pots are short, the gem still comes out and the rest is owed to the vault
+    let (to_claim_a, to_claim_b) = farm.claim_rewards_or_owe(
+        vault,
+        staked_gem,
+        (
+            ctx.accounts.reward_a_pot.amount,
+            ctx.accounts.reward_b_pot.amount,
+        ),
         now,
-        farm.reward_a.fixed_rate.schedule.denominator,
     )?;
 
     // transfer remaining rewards if any
@@ -168,6 +201,14 @@ pub fn handler(ctx: Context<WithdrawGem>) -> ProgramResult {
             to_claim_a,
         )?;
     }
+    if to_claim_b > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_b_ctx()
+                .with_signer(&[&ctx.accounts.farm.farm_seeds()]),
+            to_claim_b,
+        )?;
+    }
 
     let vault = &ctx.accounts.vault;
 
@@ -175,19 +216,28 @@ pub fn handler(ctx: Context<WithdrawGem>) -> ProgramResult {
         ctx.accounts
             .transfer_ctx()
             .with_signer(&[&vault.vault_seeds()]),
-        1, // its an nft !
-    )?;
-
-    token::close_account(
-        ctx.accounts
-            .close_gem_box_ctx()
-            .with_signer(&[&vault.vault_seeds()]),
+        amount,
     )?;
 
     let owner = &mut ctx.accounts.owner.to_account_info();
-    let vault = &mut (*ctx.accounts.vault).to_account_info();
 
-    close_account(vault, owner)?;
+    // the vault itself stays open for the rest of its gems
+    if ctx.accounts.staked_gem.gem_count == 0 {
+        token::close_account(
+            ctx.accounts
+                .close_gem_box_ctx()
+                .with_signer(&[&vault.vault_seeds()]),
+        )?;
+
+        let staked_gem = &mut (*ctx.accounts.staked_gem).to_account_info();
+        close_account(staked_gem, owner)?;
+    }
+
+    // the receipt only goes once the last of this mint has left the vault
+    if ctx.accounts.gem_deposit_receipt.gem_count == 0 {
+        let gdr = &mut (*ctx.accounts.gem_deposit_receipt).to_account_info();
+        close_account(gdr, owner)?;
+    }
 
     Ok(())
 }
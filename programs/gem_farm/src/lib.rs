@@ -3,6 +3,8 @@ use instructions::*;
 use state::*;
 
 pub mod instructions;
+pub mod merkle;
+pub mod metadata;
 pub mod number128;
 pub mod state;
 
@@ -19,35 +21,45 @@ pub mod gem_farm {
         bump_auth: u8,
         _bump_treasury: u8,
         _bump_pot_a: u8,
-        reward_type_a: RewardType,
-        reward_a_fixed_reward_schedule: Option<FixedRateSchedule>,
+        _bump_pot_b: u8,
+        args: InitFarmArgs,
         farm_config: FarmConfig,
     ) -> ProgramResult {
         msg!("init farm");
-        instructions::init_farm::handler(
-            ctx,
-            bump_auth,
-            reward_type_a,
-            reward_a_fixed_reward_schedule,
-            farm_config
-        )
+        instructions::init_farm::handler(ctx, bump_auth, args, farm_config)
     }
 
     pub fn update_farm(
         ctx: Context<UpdateFarm>,
         config: Option<FarmConfig>,
         manager: Option<Pubkey>,
+        mint_merkle_root: Option<[u8; 32]>,
     ) -> ProgramResult {
-        instructions::update_farm::handler(ctx, config, manager)
+        instructions::update_farm::handler(ctx, config, manager, mint_merkle_root)
     }
 
     pub fn fund_reward(
         ctx: Context<FundReward>,
         _bump_proof: u8,
         _bump_pot: u8,
-        amount: u64
+        amount: u64,
+        duration_sec: Option<u64>,
     ) -> ProgramResult {
-        instructions::fund_reward::handler(ctx, amount)
+        instructions::fund_reward::handler(ctx, amount, duration_sec)
+    }
+
+    pub fn cancel_reward(
+        ctx: Context<CancelReward>,
+        _bump_auth: u8,
+        _bump_pot: u8,
+    ) -> ProgramResult {
+        msg!("cancel reward");
+        instructions::cancel_reward::handler(ctx)
+    }
+
+    pub fn lock_reward(ctx: Context<LockReward>) -> ProgramResult {
+        msg!("lock reward");
+        instructions::lock_reward::handler(ctx)
     }
 
     pub fn payout_from_treasury(
@@ -60,32 +72,151 @@ pub mod gem_farm {
         instructions::treasury_payout::handler(ctx, bump_treasury, lamports)
     }
 
+    pub fn add_to_whitelist(
+        ctx: Context<AddToWhitelist>,
+        _bump: u8,
+        whitelist_type: u8,
+    ) -> ProgramResult {
+        msg!("add to whitelist");
+        instructions::add_to_whitelist::handler(ctx, whitelist_type)
+    }
+
+    pub fn remove_from_whitelist(ctx: Context<RemoveFromWhitelist>, _bump: u8) -> ProgramResult {
+        msg!("remove from whitelist");
+        instructions::remove_from_whitelist::handler(ctx)
+    }
+
+    pub fn add_to_denylist(ctx: Context<AddToDenylist>, _bump: u8) -> ProgramResult {
+        msg!("add to denylist");
+        instructions::add_to_denylist::handler(ctx)
+    }
+
+    pub fn remove_from_denylist(ctx: Context<RemoveFromDenylist>, _bump: u8) -> ProgramResult {
+        msg!("remove from denylist");
+        instructions::remove_from_denylist::handler(ctx)
+    }
+
     // --------------------------------------- farmer ops
 
     pub fn deposit_gem(
         ctx: Context<DepositGem>,
         _bump_auth: u8,
         _bump_gem_box: u8,
-        reward_a_tier_config: Option<TierConfig>,
+        _bump_staked_gem: u8,
+        _bump_gdr: u8,
+        _bump_denylist: u8,
+        args: DepositGemArgs,
     ) -> ProgramResult {
-        instructions::deposit_gem::handler(ctx, reward_a_tier_config)
+        instructions::deposit_gem::handler(ctx, args)
+    }
+
+    pub fn stake_in_place(
+        ctx: Context<StakeInPlace>,
+        _bump_auth: u8,
+        _bump_staked_gem: u8,
+        _bump_gdr: u8,
+        _bump_denylist: u8,
+        args: DepositGemArgs,
+    ) -> ProgramResult {
+        instructions::stake_in_place::handler(ctx, args)
+    }
+
+    pub fn begin_unstake(ctx: Context<BeginUnstake>, _bump_treasury: u8) -> ProgramResult {
+        instructions::begin_unstake::handler(ctx)
     }
 
     pub fn withdraw_gem(
         ctx: Context<WithdrawGem>,
         _bump_farm_auth: u8,
-        _bump_treasury: u8,
         _bump_vault_auth: u8,
         _bump_gem_box: u8,
-        _bump_pot_a: u8
+        _bump_pot_a: u8,
+        _bump_pot_b: u8,
+        amount: u64,
+    ) -> ProgramResult {
+        instructions::withdraw_gem::handler(ctx, amount)
+    }
+
+    pub fn unstake_in_place(
+        ctx: Context<UnstakeInPlace>,
+        _bump_farm_auth: u8,
+        _bump_vault_auth: u8,
+        _bump_pot_a: u8,
+        _bump_pot_b: u8,
     ) -> ProgramResult {
-        instructions::withdraw_gem::handler(ctx)
+        instructions::unstake_in_place::handler(ctx)
     }
 
-    pub fn init_vault(
-        ctx: Context<InitVault>,
-        _bump: u8
+    pub fn upgrade_tier(
+        ctx: Context<UpgradeTier>,
+        _bump_auth: u8,
+        _bump_pot: u8,
+        tier_config: TierConfig,
     ) -> ProgramResult {
+        instructions::upgrade_tier::handler(ctx, tier_config)
+    }
+
+    pub fn renew_tenure<'info>(
+        ctx: Context<'_, '_, '_, 'info, RenewTenure<'info>>,
+        _bump_auth: u8,
+        _bump_vault: u8,
+        _bump_pot_a: u8,
+        _bump_pot_b: u8,
+    ) -> ProgramResult {
+        instructions::renew_tenure::handler(ctx)
+    }
+
+    pub fn set_auto_renew(ctx: Context<SetAutoRenew>, auto_renew: bool) -> ProgramResult {
+        instructions::set_auto_renew::handler(ctx, auto_renew)
+    }
+
+    pub fn set_vault_delegate(ctx: Context<SetVaultDelegate>, delegate: Pubkey) -> ProgramResult {
+        instructions::set_vault_delegate::handler(ctx, delegate)
+    }
+
+    pub fn propose_vault_owner(
+        ctx: Context<ProposeVaultOwner>,
+        new_owner: Pubkey,
+    ) -> ProgramResult {
+        instructions::propose_vault_owner::handler(ctx, new_owner)
+    }
+
+    pub fn accept_vault_owner(ctx: Context<AcceptVaultOwner>) -> ProgramResult {
+        instructions::accept_vault_owner::handler(ctx)
+    }
+
+    pub fn freeze_vault<'info>(
+        ctx: Context<'_, '_, '_, 'info, FreezeVault<'info>>,
+    ) -> ProgramResult {
+        instructions::freeze_vault::handler(ctx)
+    }
+
+    pub fn unfreeze_vault<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnfreezeVault<'info>>,
+    ) -> ProgramResult {
+        instructions::unfreeze_vault::handler(ctx)
+    }
+
+    pub fn set_farm_paused(ctx: Context<SetFarmPaused>, paused: bool) -> ProgramResult {
+        instructions::set_farm_paused::handler(ctx, paused)
+    }
+
+    pub fn emergency_withdraw(
+        ctx: Context<EmergencyWithdraw>,
+        _bump_vault_auth: u8,
+        _bump_gem_box: u8,
+    ) -> ProgramResult {
+        instructions::emergency_withdraw::handler(ctx)
+    }
+
+    pub fn emergency_unstake_in_place(
+        ctx: Context<EmergencyUnstakeInPlace>,
+        _bump_vault_auth: u8,
+    ) -> ProgramResult {
+        instructions::emergency_unstake_in_place::handler(ctx)
+    }
+
+    pub fn init_vault(ctx: Context<InitVault>, _bump: u8) -> ProgramResult {
         instructions::init_vault::handler(ctx)
     }
 
@@ -101,11 +232,12 @@ pub mod gem_farm {
         instructions::deauthorize_funder::handler(ctx)
     }
 
-    pub fn claim_rewards(
-        ctx: Context<ClaimReward>,
+    pub fn claim_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimReward<'info>>,
         _bump_auth: u8,
         _bump_farmer: u8,
         _bump_pot_a: u8,
+        _bump_pot_b: u8,
     ) -> ProgramResult {
         instructions::claim_rewards::handler(ctx)
     }
@@ -0,0 +1,88 @@
+//! Merkle allowlists, so that large mint sets can be whitelisted with a single root
+//!
+//! Pairs are sorted before hashing (same as OpenZeppelin's MerkleProof), which means
+//! proofs don't need to carry left/right positions
+
+use anchor_lang::{prelude::*, solana_program::keccak};
+
+/// leaves commit to the mint and, optionally, to its rarity weight.
+/// a leaf's preimage is never 64 bytes, so it can't be passed off as an inner node
+pub fn mint_leaf(mint: &Pubkey, rarity_points: Option<u16>) -> [u8; 32] {
+    match rarity_points {
+        Some(rarity_points) => {
+            keccak::hashv(&[mint.as_ref(), &rarity_points.to_le_bytes()]).to_bytes()
+        }
+        None => keccak::hashv(&[mint.as_ref()]).to_bytes(),
+    }
+}
+
+pub fn verify_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed_root = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+
+    computed_root == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            keccak::hashv(&[&a, &b]).to_bytes()
+        } else {
+            keccak::hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let mints: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves = [
+            mint_leaf(&mints[0], None),
+            mint_leaf(&mints[1], Some(5)),
+            mint_leaf(&mints[2], None),
+            mint_leaf(&mints[3], Some(10)),
+        ];
+
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        assert!(verify_proof(&[leaves[0], right], root, leaves[1]));
+        assert!(verify_proof(&[leaves[3], left], root, leaves[2]));
+
+        // rarity is part of the leaf, so it can't be made up
+        assert!(!verify_proof(
+            &[leaves[0], right],
+            root,
+            mint_leaf(&mints[1], Some(6))
+        ));
+        assert!(!verify_proof(
+            &[leaves[0], right],
+            root,
+            mint_leaf(&mints[1], None)
+        ));
+
+        // mints outside the tree don't verify
+        assert!(!verify_proof(
+            &[leaves[0], right],
+            root,
+            mint_leaf(&Pubkey::new_unique(), None)
+        ));
+    }
+
+    #[test]
+    fn test_single_leaf_tree() {
+        let mint = Pubkey::new_unique();
+        let root = mint_leaf(&mint, None);
+
+        assert!(verify_proof(&[], root, mint_leaf(&mint, None)));
+        assert!(!verify_proof(&[], root, mint_leaf(&mint, Some(1))));
+    }
+}
@@ -0,0 +1,205 @@
+//! Metaplex metadata fields and instructions newer than the metaplex crate we depend on
+//!
+//! Metaplex appended token_standard and collection after edition_nonce, in what used to
+//! be padding. Old accounts have zeroes there, which conveniently deserialize as None
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke_signed},
+};
+use gem_common::errors::ErrorCode;
+use metaplex_token_metadata::state::Metadata;
+
+// MetadataInstruction variants, borsh encodes them as a single byte
+const FREEZE_DELEGATED_ACCOUNT: u8 = 26;
+const THAW_DELEGATED_ACCOUNT: u8 = 27;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct MetadataCollection {
+    pub verified: bool,
+
+    pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct MetadataExtension {
+    /// TokenStandard enum - we only need to skip over it
+    pub token_standard: Option<u8>,
+
+    pub collection: Option<MetadataCollection>,
+}
+
+impl MetadataExtension {
+    /// metadata_data is the full account data that metadata was deserialized from
+    pub fn read(metadata_data: &[u8], metadata: &Metadata) -> Result<Self, ProgramError> {
+        // names etc are variable length, so re-serialize to find out where the known fields end
+        let offset = metadata.try_to_vec()?.len();
+
+        let mut extension_data = metadata_data
+            .get(offset..)
+            .ok_or(ErrorCode::WrongMetadata)?;
+
+        Self::deserialize(&mut extension_data).map_err(|_| ErrorCode::WrongMetadata.into())
+    }
+
+    /// only counts if the collection's authority has signed off on the NFT being part of it,
+    /// otherwise anyone could claim to be in any collection
+    pub fn verified_collection(&self) -> Option<Pubkey> {
+        self.collection
+            .as_ref()
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key)
+    }
+}
+
+/// NFT mints have their master edition as freeze authority, so it's metaplex that freezes
+/// on behalf of the token account's delegate. metaplex checks the edition belongs to the mint
+fn delegated_account_ix(
+    instruction: u8,
+    delegate: &AccountInfo,
+    token_account: &AccountInfo,
+    edition: &AccountInfo,
+    mint: &AccountInfo,
+    token_program: &AccountInfo,
+) -> Instruction {
+    Instruction {
+        program_id: metaplex_token_metadata::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(delegate.key(), true),
+            AccountMeta::new(token_account.key(), false),
+            AccountMeta::new_readonly(edition.key(), false),
+            AccountMeta::new_readonly(mint.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+        ],
+        data: vec![instruction],
+    }
+}
+
+pub fn freeze_delegated_account<'info>(
+    metadata_program: &AccountInfo<'info>,
+    delegate: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &delegated_account_ix(
+            FREEZE_DELEGATED_ACCOUNT,
+            delegate,
+            token_account,
+            edition,
+            mint,
+            token_program,
+        ),
+        &[
+            delegate.clone(),
+            token_account.clone(),
+            edition.clone(),
+            mint.clone(),
+            token_program.clone(),
+            metadata_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+pub fn thaw_delegated_account<'info>(
+    metadata_program: &AccountInfo<'info>,
+    delegate: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &delegated_account_ix(
+            THAW_DELEGATED_ACCOUNT,
+            delegate,
+            token_account,
+            edition,
+            mint,
+            token_program,
+        ),
+        &[
+            delegate.clone(),
+            token_account.clone(),
+            edition.clone(),
+            mint.clone(),
+            token_program.clone(),
+            metadata_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metaplex_token_metadata::state::{Data, Key, MAX_METADATA_LEN};
+
+    fn metadata() -> Metadata {
+        Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            data: Data {
+                name: "gem".to_string(),
+                symbol: "GEM".to_string(),
+                uri: "https://gem.farm".to_string(),
+                seller_fee_basis_points: 500,
+                creators: None,
+            },
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: Some(255),
+        }
+    }
+
+    fn account_data(metadata: &Metadata, extension: Option<&MetadataExtension>) -> Vec<u8> {
+        let mut data = metadata.try_to_vec().unwrap();
+        if let Some(extension) = extension {
+            data.extend(extension.try_to_vec().unwrap());
+        }
+        data.resize(MAX_METADATA_LEN, 0);
+        data
+    }
+
+    #[test]
+    fn test_read_old_metadata() {
+        let metadata = metadata();
+        let data = account_data(&metadata, None);
+
+        let extension = MetadataExtension::read(&data, &metadata).unwrap();
+        assert_eq!(extension.token_standard, None);
+        assert_eq!(extension.verified_collection(), None);
+    }
+
+    #[test]
+    fn test_read_collection() {
+        let metadata = metadata();
+        let key = Pubkey::new_unique();
+
+        let mut extension = MetadataExtension {
+            token_standard: Some(0),
+            collection: Some(MetadataCollection {
+                verified: true,
+                key,
+            }),
+        };
+        let data = account_data(&metadata, Some(&extension));
+        assert_eq!(
+            MetadataExtension::read(&data, &metadata).unwrap(),
+            extension
+        );
+        assert_eq!(extension.verified_collection(), Some(key));
+
+        // unverified collections don't count
+        extension.collection.as_mut().unwrap().verified = false;
+        let data = account_data(&metadata, Some(&extension));
+        let read = MetadataExtension::read(&data, &metadata).unwrap();
+        assert_eq!(read.verified_collection(), None);
+    }
+}
@@ -76,6 +76,37 @@ impl Number128 {
         }
     }
 
+    /// numerator / denominator, without going through try_div - that scales the numerator
+    /// up by ONE twice, which overflows for token amounts past ~3.4e8 base units
+    pub fn from_ratio(numerator: u64, denominator: u64) -> Result<Self, ProgramError> {
+        let n = (numerator as u128)
+            .checked_mul(ONE)
+            .and_then(|scaled| scaled.checked_div(denominator as u128))
+            .ok_or_else(|| {
+                msg!("tried dividing {} by {}", numerator, denominator);
+                ProgramError::from(ErrorCode::ArithmeticError)
+            })?;
+        Ok(Self { n })
+    }
+
+    /// multiplies by a plain integer, which (unlike try_mul) only scales by ONE once
+    pub fn try_mul_int(self, rhs: u64) -> Result<Self, ProgramError> {
+        let n = self.n.checked_mul(rhs as u128).ok_or_else(|| {
+            msg!("tried multiplying {} and {}", self, rhs);
+            ProgramError::from(ErrorCode::ArithmeticError)
+        })?;
+        Ok(Self { n })
+    }
+
+    /// divides by a plain integer, see try_mul_int
+    pub fn try_div_int(self, rhs: u64) -> Result<Self, ProgramError> {
+        let n = self.n.checked_div(rhs as u128).ok_or_else(|| {
+            msg!("tried dividing {} by {}", self, rhs);
+            ProgramError::from(ErrorCode::ArithmeticError)
+        })?;
+        Ok(Self { n })
+    }
+
     fn ten_pow(exponent: u32) -> u128 {
         let value: u64 = match exponent {
             16 => 10_000_000_000_000_000,
@@ -245,6 +276,16 @@ mod tests2 {
         assert_eq!(Number128::ONE, Number128::from(1u64));
     }
 
+    #[test]
+    fn integer_ops_dont_overflow_on_large_amounts() {
+        // 1e18 base units / 1 day would overflow try_div, which scales by ONE twice
+        let rate = Number128::from_ratio(1_000_000_000_000_000_000, 86_400).unwrap();
+        let accrued = rate.try_mul_int(86_400).unwrap().try_div_int(2).unwrap();
+
+        assert_eq!(accrued.as_u64(0).unwrap(), 499_999_999_999_999_999);
+        assert!(Number128::from_ratio(1, 0).is_err());
+    }
+
     #[test]
     fn one_plus_one_equals_two() {
         assert_eq!(
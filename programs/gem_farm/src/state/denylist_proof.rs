@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+
+/// if this PDA exists, the mint recorded below can't be deposited into the farm recorded below,
+/// regardless of which whitelists it might be on (stolen / exploit-minted / team-reserved gems)
+#[repr(C)]
+#[account]
+pub struct DenylistProof {
+    pub denylisted_mint: Pubkey,
+
+    pub farm: Pubkey,
+}
@@ -9,7 +9,12 @@ pub const LATEST_FARM_VERSION: u16 = 0;
 #[derive(Debug, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct FarmConfig {
     pub paper_hands_tax_lamp: u64,
-    pub whitelisted_candy_machine: Option<Pubkey>
+
+    /// how long a gem has to stay staked before unstaking can begin
+    pub min_staking_sec: u64,
+
+    /// how long after beginning to unstake the gem can actually be withdrawn
+    pub cooldown_sec: u64,
 }
 
 #[repr(C)]
@@ -38,14 +43,51 @@ pub struct Farm {
     /// how many accounts can create funding schedules
     pub authorized_funder_count: u64,
 
+    /// how many creators / mints / update authorities / collections have been whitelisted (via WhitelistProofs).
+    /// as long as there's at least one, deposits have to match a whitelist
+    pub whitelisted_creators: u32,
+
+    pub whitelisted_mints: u32,
+
+    pub whitelisted_update_authorities: u32,
+
+    pub whitelisted_collections: u32,
+
+    /// root of a merkle tree of allowed mints (see merkle.rs), all zeroes if not in use.
+    /// lets large mint sets be allowlisted without a WhitelistProof per mint
+    pub mint_merkle_root: [u8; 32],
+
+    /// set by the farm manager to halt deposits, claims and funding, eg if a bug is found.
+    /// gems can still be taken out via emergency_withdraw
+    pub paused: bool,
+
     // ----------------- rewards
     pub reward_a: FarmReward,
 
+    pub reward_b: FarmReward,
+
     /// total vault count registered with this bank
     pub vault_count: u64,
+
+    /// sum of rarity points across all staked gems, used to split variable rewards
+    /// (every unit staked counts as a single rarity point, unless the mint's
+    /// merkle allowlist leaf gives it a different weight)
+    pub rarity_points_staked: u64,
 }
 
 impl Farm {
+    pub fn has_mint_merkle_root(&self) -> bool {
+        self.mint_merkle_root != [0; 32]
+    }
+
+    pub fn assert_not_paused(&self) -> ProgramResult {
+        if self.paused {
+            return Err(ErrorCode::FarmPaused.into());
+        }
+
+        Ok(())
+    }
+
     pub fn farm_seeds(&self) -> [&[u8]; 2] {
         [
             self.farm_authority_seed.as_ref(),
@@ -53,71 +95,400 @@ impl Farm {
         ]
     }
 
-    pub fn fund_reward(&mut self, amount: u64) -> ProgramResult {
-        self.reward_a.funds.total_funded.try_add_assign(amount)?;
+    pub fn match_reward_by_mint(
+        &mut self,
+        reward_mint: Pubkey,
+    ) -> Result<&mut FarmReward, ProgramError> {
+        if reward_mint == self.reward_a.reward_mint {
+            return Ok(&mut self.reward_a);
+        }
 
-        Ok(())
+        if reward_mint == self.reward_b.reward_mint {
+            return Ok(&mut self.reward_b);
+        }
+
+        Err(ErrorCode::UnknownRewardMint.into())
     }
 
-    pub fn reserve_rewards(
+    /// same as match_reward_by_mint, but also returns the staked gem's side of the reward
+    pub fn match_rewards_by_mint<'a>(
+        &'a mut self,
+        staked_gem: &'a mut StakedGem,
+        reward_mint: Pubkey,
+    ) -> Result<(&'a mut FarmReward, &'a mut VaultReward), ProgramError> {
+        if reward_mint == self.reward_a.reward_mint {
+            return Ok((&mut self.reward_a, &mut staked_gem.reward_a));
+        }
+
+        if reward_mint == self.reward_b.reward_mint {
+            return Ok((&mut self.reward_b, &mut staked_gem.reward_b));
+        }
+
+        Err(ErrorCode::UnknownRewardMint.into())
+    }
+
+    pub fn fund_reward(
+        &mut self,
+        now: u64,
+        reward_mint: Pubkey,
+        amount: u64,
+        duration_sec: Option<u64>,
+    ) -> ProgramResult {
+        let farm_rarity_points_staked = self.rarity_points_staked;
+
+        self.match_reward_by_mint(reward_mint)?.fund_reward(
+            now,
+            amount,
+            duration_sec,
+            farm_rarity_points_staked,
+        )
+    }
+
+    pub fn cancel_reward_by_mint(
+        &mut self,
+        now: u64,
+        reward_mint: Pubkey,
+    ) -> Result<u64, ProgramError> {
+        let farm_rarity_points_staked = self.rarity_points_staked;
+
+        self.match_reward_by_mint(reward_mint)?
+            .cancel_reward(now, farm_rarity_points_staked)
+    }
+
+    pub fn lock_reward_by_mint(&mut self, now: u64, reward_mint: Pubkey) -> ProgramResult {
+        self.match_reward_by_mint(reward_mint)?.lock_reward(now)
+    }
+
+    /// brings variable accruals up to date, optionally settling a single staked gem along
+    /// the way. needs to be called before anything that changes rarity points staked or the rate
+    pub fn update_rewards(
+        &mut self,
+        now: u64,
+        staked_gem: Option<&mut StakedGem>,
+    ) -> ProgramResult {
+        let farm_rarity_points_staked = self.rarity_points_staked;
+
+        match staked_gem {
+            Some(staked_gem) => {
+                let gem_rarity_points = staked_gem.rarity_points;
+
+                self.reward_a.update_accrued_reward(
+                    now,
+                    farm_rarity_points_staked,
+                    Some((&mut staked_gem.reward_a, gem_rarity_points)),
+                )?;
+                self.reward_b.update_accrued_reward(
+                    now,
+                    farm_rarity_points_staked,
+                    Some((&mut staked_gem.reward_b, gem_rarity_points)),
+                )
+            }
+            None => {
+                self.reward_a
+                    .update_accrued_reward(now, farm_rarity_points_staked, None)?;
+                self.reward_b
+                    .update_accrued_reward(now, farm_rarity_points_staked, None)
+            }
+        }
+    }
+
+    pub fn stake_rarity_points(
+        &mut self,
+        vault: &mut Vault,
+        staked_gem: &mut StakedGem,
+        rarity_points: u64,
+    ) -> ProgramResult {
+        self.rarity_points_staked.try_add_assign(rarity_points)?;
+        vault.rarity_points_staked.try_add_assign(rarity_points)?;
+        staked_gem.rarity_points.try_add_assign(rarity_points)
+    }
+
+    pub fn unstake_rarity_points(
         &mut self,
         vault: &mut Vault,
+        staked_gem: &mut StakedGem,
+        rarity_points: u64,
+    ) -> ProgramResult {
+        self.rarity_points_staked.try_sub_assign(rarity_points)?;
+        vault.rarity_points_staked.try_sub_assign(rarity_points)?;
+        staked_gem.rarity_points.try_sub_assign(rarity_points)
+    }
+
+    pub fn reserve_rewards(
+        &mut self,
+        staked_gem: &mut StakedGem,
         now: u64,
         reward_a_tier_config: Option<TierConfig>,
+        reward_b_tier_config: Option<TierConfig>,
     ) -> ProgramResult {
-        // Immediately add vault count on this farm.
-        self.vault_count.try_add_assign(1)?;
+        let gem_count = staked_gem.gem_count;
 
-        // let's ignore reward b for now and focus on reward a
-        vault.reward_a.staked_at = now;
-        vault.reward_a.last_rewards_claimed_at = now;
+        self.reward_a.reserve_reward(
+            &mut staked_gem.reward_a,
+            now,
+            gem_count,
+            reward_a_tier_config,
+        )?;
+        self.reward_b.reserve_reward(
+            &mut staked_gem.reward_b,
+            now,
+            gem_count,
+            reward_b_tier_config,
+        )
+    }
 
-        if reward_a_tier_config.is_some() {
-            vault.reward_a.reward_tier = reward_a_tier_config.unwrap();
-        } else {
-            vault.reward_a.reward_tier = self.reward_a.fixed_rate.schedule.tier0;
+    /// expects rewards up to now to have been claimed already
+    pub fn renew_tenures(
+        &mut self,
+        vault: &Vault,
+        staked_gem: &mut StakedGem,
+        now: u64,
+    ) -> ProgramResult {
+        if !vault.auto_renew || staked_gem.unstaking {
+            return Ok(());
         }
 
-        let tier = vault.reward_a.reward_tier;
+        self.reward_a.renew_tenure(&mut staked_gem.reward_a, now)?;
+        self.reward_b.renew_tenure(&mut staked_gem.reward_b, now)
+    }
 
-        // be sure to divide by denominator
-        let reserved_amount = tier
-            .reward_rate
-            .try_div(self.reward_a.fixed_rate.schedule.denominator)?
-            .try_mul(tier.required_tenure)?;
-
-        msg!(
-            "Funded amount in rewards, {}",
-            self.reward_a.funds.pending_amount()?
-        );
-        // check the farm funds. we need to have the reserved rewards in farm fund
-        if reserved_amount > self.reward_a.funds.pending_amount()? {
-            return Err(ErrorCode::InsufficientFunding.into());
+    pub fn unreserve_rewards(&mut self, staked_gem: &StakedGem, now: u64) -> ProgramResult {
+        self.reward_a.unreserve_reward(&staked_gem.reward_a, now)?;
+        self.reward_b.unreserve_reward(&staked_gem.reward_b, now)
+    }
+
+    pub fn add_whitelist_counts(&mut self, whitelist_type: WhitelistType) -> ProgramResult {
+        if whitelist_type.contains(WhitelistType::CREATOR) {
+            self.whitelisted_creators.try_add_assign(1)?;
+        }
+        if whitelist_type.contains(WhitelistType::MINT) {
+            self.whitelisted_mints.try_add_assign(1)?;
+        }
+        if whitelist_type.contains(WhitelistType::UPDATE_AUTHORITY) {
+            self.whitelisted_update_authorities.try_add_assign(1)?;
+        }
+        if whitelist_type.contains(WhitelistType::COLLECTION) {
+            self.whitelisted_collections.try_add_assign(1)?;
         }
 
-        vault.reward_a.reserved_amount = reserved_amount;
+        Ok(())
+    }
 
-        // update farm reserves
-        self.reward_a
-            .funds
-            .total_accrued_to_stakers
-            .try_add_assign(reserved_amount)?;
+    pub fn remove_whitelist_counts(&mut self, whitelist_type: WhitelistType) -> ProgramResult {
+        if whitelist_type.contains(WhitelistType::CREATOR) {
+            self.whitelisted_creators.try_sub_assign(1)?;
+        }
+        if whitelist_type.contains(WhitelistType::MINT) {
+            self.whitelisted_mints.try_sub_assign(1)?;
+        }
+        if whitelist_type.contains(WhitelistType::UPDATE_AUTHORITY) {
+            self.whitelisted_update_authorities.try_sub_assign(1)?;
+        }
+        if whitelist_type.contains(WhitelistType::COLLECTION) {
+            self.whitelisted_collections.try_sub_assign(1)?;
+        }
 
         Ok(())
     }
 
-    pub fn unreserve_rewards(&mut self, vault: &mut Vault, now: u64) -> ProgramResult {
-        // amount we unreserve is total paid out + any outstanding rewards to be paid out
-        let unreserve_amount = vault.reward_a.paid_out_reward.try_add(
-            vault
-                .reward_a
-                .outstanding_reward(now, self.reward_a.fixed_rate.schedule.denominator)?,
+    /// starts a freshly deposited gem earning, whichever way it's being held.
+    /// gem_count has to already be set on the staked gem
+    pub fn stake_gem(
+        &mut self,
+        vault: &mut Vault,
+        staked_gem: &mut StakedGem,
+        now: u64,
+        rarity_points: u64,
+        reward_a_tier_config: Option<TierConfig>,
+        reward_b_tier_config: Option<TierConfig>,
+    ) -> ProgramResult {
+        // variable rewards accrued so far belong to those already staked
+        self.update_rewards(now, Some(staked_gem))?;
+
+        // 1. calculate how much we're reserving for this deposited gem (scaled by the amount)
+        // 2. record that amount on the farm reward (rewardA and rewardB)
+        // 3. record the deposit time
+        // 4. record the deposit tier the user selected
+        self.reserve_rewards(staked_gem, now, reward_a_tier_config, reward_b_tier_config)?;
+
+        self.stake_rarity_points(vault, staked_gem, rarity_points)?;
+        self.add_gem_to_vault(vault)
+    }
+
+    /// deposits more of a mint that's already staked. whatever the gem earned so far is added
+    /// to what the vault is owed (claim_rewards pays it out), then all of it is staked afresh on
+    /// the tiers passed in, same as a new deposit would be. a gem that's still staked can't use
+    /// this to get out of its tenure early though.
+    /// rarity_points are for the whole gem_count after the top up
+    pub fn top_up_gem(
+        &mut self,
+        vault: &mut Vault,
+        staked_gem: &mut StakedGem,
+        now: u64,
+        amount: u64,
+        rarity_points: u64,
+        (reward_a_tier_config, reward_b_tier_config): (Option<TierConfig>, Option<TierConfig>),
+    ) -> ProgramResult {
+        // a gem that's unstaking already stopped earning, and gave all this up in begin_unstake
+        if !staked_gem.unstaking {
+            self.reward_a.assert_commits_further(
+                &staked_gem.reward_a,
+                now,
+                reward_a_tier_config,
+            )?;
+            self.reward_b.assert_commits_further(
+                &staked_gem.reward_b,
+                now,
+                reward_b_tier_config,
+            )?;
+
+            self.update_rewards(now, Some(staked_gem))?;
+            self.unreserve_rewards(staked_gem, now)?;
+
+            let rarity_points = staked_gem.rarity_points;
+            self.unstake_rarity_points(vault, staked_gem, rarity_points)?;
+        }
+
+        // no pots at hand, so it's all owed
+        self.claim_rewards_or_owe(vault, staked_gem, (0, 0), now)?;
+
+        staked_gem.gem_count.try_add_assign(amount)?;
+        staked_gem.unstaking = false;
+        staked_gem.unstaked_at = 0;
+        staked_gem.cooldown_ends_ts = 0;
+        staked_gem.reward_a = VaultReward::default();
+        staked_gem.reward_b = VaultReward::default();
+
+        // from here on it's the same as stake_gem, except the vault already counts the gem
+        self.update_rewards(now, Some(staked_gem))?;
+        self.reserve_rewards(staked_gem, now, reward_a_tier_config, reward_b_tier_config)?;
+        self.stake_rarity_points(vault, staked_gem, rarity_points)
+    }
+
+    /// settles a gem on its way out of the farm. it has to come out even if the pots have
+    /// run dry (or its tier0 accrual has outrun pending funds), so whatever can't be covered
+    /// is added to what the vault is owed instead.
+    /// returns the amounts of reward a and b to transfer out of the pots now
+    pub fn claim_rewards_or_owe(
+        &mut self,
+        vault: &mut Vault,
+        staked_gem: &mut StakedGem,
+        pot_balances: (u64, u64),
+        now: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        let (pot_a_balance, pot_b_balance) = pot_balances;
+
+        // earned up to when unstaking began
+        let accrual_end = staked_gem.accrual_upper_bound(now);
+
+        let (to_claim_a, owed_a) = self.reward_a.claim_reward_or_owe(
+            &mut staked_gem.reward_a,
+            pot_a_balance,
+            accrual_end,
+        )?;
+        let (to_claim_b, owed_b) = self.reward_b.claim_reward_or_owe(
+            &mut staked_gem.reward_b,
+            pot_b_balance,
+            accrual_end,
         )?;
 
-        self.reward_a
+        if owed_a > 0 || owed_b > 0 {
+            msg!("couldn't cover it all, owing {} A and {} B", owed_a, owed_b);
+        }
+        vault.reward_a_owed.try_add_assign(owed_a)?;
+        vault.reward_b_owed.try_add_assign(owed_b)?;
+
+        Ok((to_claim_a, to_claim_b))
+    }
+
+    /// pays back as much of what the vault is owed as the pots can, without touching anyone
+    /// else's share. returns the amounts of reward a and b to transfer out of the pots
+    pub fn pay_owed_rewards(
+        &mut self,
+        vault: &mut Vault,
+        pot_balances: (u64, u64),
+    ) -> Result<(u64, u64), ProgramError> {
+        let (pot_a_balance, pot_b_balance) = pot_balances;
+
+        let to_pay_a = self
+            .reward_a
             .funds
-            .total_accrued_to_stakers
-            .try_sub_assign(unreserve_amount)?;
+            .pay_owed(vault.reward_a_owed, pot_a_balance)?;
+        let to_pay_b = self
+            .reward_b
+            .funds
+            .pay_owed(vault.reward_b_owed, pot_b_balance)?;
+
+        vault.pay_owed_rewards(to_pay_a, to_pay_b)
+    }
+
+    /// emergency withdrawal counterpart of claim_rewards_or_owe - nothing's paid out, and
+    /// whatever the gem still had coming goes back into pending funds
+    pub fn forfeit_rewards(&mut self, staked_gem: &mut StakedGem) -> ProgramResult {
+        let unstaked_at = if staked_gem.unstaking {
+            Some(staked_gem.unstaked_at)
+        } else {
+            None
+        };
+
+        self.reward_a
+            .forfeit_reward(&mut staked_gem.reward_a, unstaked_at)?;
+        self.reward_b
+            .forfeit_reward(&mut staked_gem.reward_b, unstaked_at)
+    }
+
+    /// stops a gem earning while its vault is frozen. variable rewards are settled up to now,
+    /// then its rarity points stop counting towards the farm's (the vault keeps them)
+    pub fn pause_gem_accrual(&mut self, staked_gem: &mut StakedGem, now: u64) -> ProgramResult {
+        // unstaking gems have stopped earning already
+        if staked_gem.unstaking {
+            return Ok(());
+        }
+
+        self.update_rewards(now, Some(staked_gem))?;
+        self.rarity_points_staked
+            .try_sub_assign(staked_gem.rarity_points)
+    }
+
+    /// picks up where pause_gem_accrual left off, as if the frozen period never happened
+    pub fn resume_gem_accrual(
+        &mut self,
+        staked_gem: &mut StakedGem,
+        frozen_at: u64,
+        now: u64,
+    ) -> ProgramResult {
+        if staked_gem.unstaking {
+            return Ok(());
+        }
+
+        // whatever accrued while frozen went to everyone else
+        self.update_rewards(now, None)?;
+
+        let paused_sec = now.try_sub(frozen_at)?;
+        self.reward_a
+            .resume_reward(&mut staked_gem.reward_a, paused_sec)?;
+        self.reward_b
+            .resume_reward(&mut staked_gem.reward_b, paused_sec)?;
+
+        self.rarity_points_staked
+            .try_add_assign(staked_gem.rarity_points)
+    }
+
+    /// a vault counts towards vault_count for as long as it holds at least one gem
+    pub fn add_gem_to_vault(&mut self, vault: &mut Vault) -> ProgramResult {
+        if vault.gem_count == 0 {
+            self.vault_count.try_add_assign(1)?;
+        }
+
+        vault.gem_count.try_add_assign(1)
+    }
+
+    pub fn remove_gem_from_vault(&mut self, vault: &mut Vault) -> ProgramResult {
+        vault.gem_count.try_sub_assign(1)?;
+
+        if vault.gem_count == 0 {
+            self.vault_count.try_sub_assign(1)?;
+        }
 
         Ok(())
     }
@@ -132,7 +503,7 @@ pub enum RewardType {
     Fixed,
 }
 
-/// these numbers should only ever go up - ie they are cummulative
+/// these numbers should only ever go up - ie they are cummulative (except owed_to_stakers)
 #[repr(C)]
 #[derive(Debug, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct FundsTracker {
@@ -140,14 +511,38 @@ pub struct FundsTracker {
 
     pub total_refunded: u64,
 
+    /// can run ahead of what's been funded, when tier0 accrual outruns pending funds and is
+    /// owed instead - the next funding round covers that first
     pub total_accrued_to_stakers: u64,
+
+    /// the part of total_accrued_to_stakers that vaults are owed (see Vault::reward_a_owed)
+    /// and hasn't been paid out yet
+    pub owed_to_stakers: u64,
 }
 
 impl FundsTracker {
     pub fn pending_amount(&self) -> Result<u64, ProgramError> {
-        self.total_funded
+        Ok(self
+            .total_funded
             .try_sub(self.total_refunded)?
-            .try_sub(self.total_accrued_to_stakers)
+            .saturating_sub(self.total_accrued_to_stakers))
+    }
+
+    /// pays out as much of a vault's owed amount as a pot holding pot_balance can. everyone
+    /// else's unpaid share of what's accrued is in there too and has to stay put, which leaves
+    /// what's been funded beyond that - less whatever accrual ran ahead of funding.
+    /// returns the amount to transfer out of the pot
+    pub fn pay_owed(&mut self, owed: u64, pot_balance: u64) -> Result<u64, ProgramError> {
+        let backed = self
+            .total_funded
+            .try_sub(self.total_refunded)?
+            .try_add(self.owed_to_stakers)?
+            .saturating_sub(self.total_accrued_to_stakers);
+
+        let to_pay = std::cmp::min(owed, std::cmp::min(backed, pot_balance));
+        self.owed_to_stakers.try_sub_assign(to_pay)?;
+
+        Ok(to_pay)
     }
 }
 
@@ -161,11 +556,41 @@ pub struct TimeTracker {
     pub reward_end_ts: u64,
 
     /// this will be set = to reward_end_ts if farm manager decides to lock up their reward
-    /// gives stakers the certainty it won't be withdrawn
+    /// gives stakers the certainty it won't be withdrawn. u64::MAX for a fixed reward with no end
     pub lock_end_ts: u64,
 }
 
-impl TimeTracker {}
+impl TimeTracker {
+    /// rewards can't accrue past the end of the funded period
+    pub fn reward_upper_bound(&self, now: u64) -> u64 {
+        std::cmp::min(self.reward_end_ts, now)
+    }
+
+    /// fixed rewards funded without a duration never end (reward_end_ts stays 0).
+    /// otherwise accrual stops at the end of the funded period, except for tenures
+    /// that were already promised beyond it
+    pub fn fixed_reward_upper_bound(&self, now: u64, tenure_expiry: u64) -> u64 {
+        if self.reward_end_ts == 0 {
+            return now;
+        }
+
+        std::cmp::min(now, std::cmp::max(self.reward_end_ts, tenure_expiry))
+    }
+
+    pub fn reward_ended(&self, now: u64) -> bool {
+        self.reward_end_ts > 0 && now >= self.reward_end_ts
+    }
+
+    /// a new funding round picks up where the previous one ends (or now, if it already has)
+    pub fn extend_reward(&mut self, now: u64, duration_sec: u64) -> ProgramResult {
+        let start = std::cmp::max(self.reward_end_ts, now);
+
+        self.reward_end_ts = start.try_add(duration_sec)?;
+        self.duration_sec.try_add_assign(duration_sec)?;
+
+        Ok(())
+    }
+}
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
@@ -183,9 +608,1009 @@ pub struct FarmReward {
     /// only one of these two (fixed and variable) will actually be used, per reward
     pub fixed_rate: FixedRateReward,
 
+    pub variable_rate: VariableRateReward,
+
     pub funds: FundsTracker,
 
     pub times: TimeTracker,
 }
 
-impl FarmReward {}
+impl FarmReward {
+    /// duration_sec works the same for both reward types: the new round is appended to the
+    /// current one, so the reward ends duration_sec after max(reward_end_ts, now). it's optional
+    /// for fixed rewards (no duration = no end), and required for variable ones
+    pub fn fund_reward(
+        &mut self,
+        now: u64,
+        amount: u64,
+        duration_sec: Option<u64>,
+        farm_rarity_points_staked: u64,
+    ) -> ProgramResult {
+        match self.reward_type {
+            RewardType::Fixed => {
+                self.funds.total_funded.try_add_assign(amount)?;
+
+                if let Some(duration_sec) = duration_sec {
+                    self.times.extend_reward(now, duration_sec)?;
+                }
+            }
+            RewardType::Variable => {
+                // variable rewards need to know over which period to spread the funding
+                let duration_sec = match duration_sec {
+                    Some(duration_sec) if duration_sec > 0 => duration_sec,
+                    _ => return Err(ErrorCode::InvalidParameter.into()),
+                };
+
+                // settle everything accrued at the old rate before changing it
+                self.update_accrued_reward(now, farm_rarity_points_staked, None)?;
+
+                self.variable_rate.fund_reward(
+                    now,
+                    &mut self.times,
+                    &mut self.funds,
+                    VariableRateConfig {
+                        amount,
+                        duration_sec,
+                    },
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// refunds everything not yet promised to stakers, returns the amount to transfer out
+    pub fn cancel_reward(
+        &mut self,
+        now: u64,
+        farm_rarity_points_staked: u64,
+    ) -> Result<u64, ProgramError> {
+        if self.times.lock_end_ts > now {
+            return Err(ErrorCode::RewardLocked.into());
+        }
+
+        // settle what stakers earned up to this point, they keep that
+        self.update_accrued_reward(now, farm_rarity_points_staked, None)?;
+
+        let refund_amount = self.funds.pending_amount()?;
+        self.funds.total_refunded.try_add_assign(refund_amount)?;
+
+        if matches!(self.reward_type, RewardType::Variable) {
+            self.variable_rate.cancel_reward(now, &mut self.times);
+        }
+
+        Ok(refund_amount)
+    }
+
+    pub fn lock_reward(&mut self, now: u64) -> ProgramResult {
+        // a fixed reward funded without a duration has no end, so it's locked for good -
+        // as long as there's something in it to lock
+        let open_ended =
+            matches!(self.reward_type, RewardType::Fixed) && self.times.reward_end_ts == 0;
+
+        let lock_until = if open_ended {
+            if self.funds.total_funded == self.funds.total_refunded {
+                return Err(ErrorCode::InvalidParameter.into());
+            }
+            u64::MAX
+        } else {
+            // nothing to lock if the reward isn't running
+            if self.times.reward_end_ts <= now {
+                return Err(ErrorCode::InvalidParameter.into());
+            }
+            self.times.reward_end_ts
+        };
+
+        // a lock can only ever be extended, never shortened
+        self.times.lock_end_ts = std::cmp::max(self.times.lock_end_ts, lock_until);
+
+        Ok(())
+    }
+
+    pub fn update_accrued_reward(
+        &mut self,
+        now: u64,
+        farm_rarity_points_staked: u64,
+        vault_reward: Option<(&mut VaultReward, u64)>,
+    ) -> ProgramResult {
+        // fixed rewards are reserved at deposit and computed on claim, nothing to update
+        if matches!(self.reward_type, RewardType::Fixed) {
+            return Ok(());
+        }
+
+        self.variable_rate.update_accrued_reward(
+            now,
+            &self.times,
+            &mut self.funds,
+            farm_rarity_points_staked,
+            vault_reward,
+        )
+    }
+
+    pub fn assert_valid_deposit(&self, now: u64, tier_config: Option<TierConfig>) -> ProgramResult {
+        if self.times.reward_ended(now) {
+            return Err(ErrorCode::RewardEnded.into());
+        }
+
+        let tier_config = match tier_config {
+            Some(tier_config) => tier_config,
+            None => return Ok(()),
+        };
+
+        // tiers mean nothing for variable rewards
+        if matches!(self.reward_type, RewardType::Variable) {
+            return Err(ErrorCode::InvalidTierConfig.into());
+        }
+
+        self.fixed_rate
+            .schedule
+            .assert_valid_tier_config(tier_config)?;
+
+        // the whole tenure has to fit inside the funded window
+        if self.times.reward_end_ts > 0
+            && now.try_add(tier_config.required_tenure)? > self.times.reward_end_ts
+        {
+            return Err(ErrorCode::TenureExceedsRewardEnd.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn reserve_reward(
+        &mut self,
+        vault_reward: &mut VaultReward,
+        now: u64,
+        gem_count: u64,
+        tier_config: Option<TierConfig>,
+    ) -> ProgramResult {
+        vault_reward.staked_at = now;
+        vault_reward.last_rewards_claimed_at = now;
+        vault_reward.gem_count = gem_count;
+
+        // variable rewards accrue as they go, there's nothing to reserve upfront
+        if matches!(self.reward_type, RewardType::Variable) {
+            vault_reward.reward_tier = TierConfig::default();
+            return Ok(());
+        }
+
+        vault_reward.reward_tier = tier_config.unwrap_or(self.fixed_rate.schedule.tier0);
+
+        let tier = vault_reward.reward_tier;
+
+        // be sure to divide by denominator
+        let reserved_amount = tier.reward_over(
+            gem_count,
+            tier.required_tenure,
+            self.fixed_rate.schedule.denominator,
+        )?;
+
+        msg!("Funded amount in rewards, {}", self.funds.pending_amount()?);
+        // check the farm funds. we need to have the reserved rewards in farm fund
+        if reserved_amount > self.funds.pending_amount()? {
+            return Err(ErrorCode::InsufficientFunding.into());
+        }
+
+        vault_reward.reserved_amount = reserved_amount;
+
+        // update farm reserves
+        self.funds
+            .total_accrued_to_stakers
+            .try_add_assign(reserved_amount)?;
+
+        Ok(())
+    }
+
+    /// moves a staked vault onto a (usually longer) tier, starting a new tenure from now.
+    /// returns what was earned on the previous tier, same as claim_reward: (amount to transfer
+    /// out of the pot, amount owed to the vault)
+    pub fn upgrade_tier(
+        &mut self,
+        vault_reward: &mut VaultReward,
+        now: u64,
+        tier_config: TierConfig,
+        pot_balance: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        // same checks as a fresh deposit would go through
+        self.assert_valid_deposit(now, Some(tier_config))?;
+
+        // can only ever commit further, never shorten the current commitment
+        self.assert_commits_further(vault_reward, now, Some(tier_config))?;
+
+        let schedule = self.fixed_rate.schedule;
+        let denominator = schedule.denominator;
+
+        // 1. settle whatever was earned on the current tier
+        let claimed = self.claim_reward(vault_reward, pot_balance, now)?;
+
+        // 2. the unearned part of the current reservation goes towards the new one,
+        //    only the difference has to come out of pending funds
+        let unearned = vault_reward.unearned_reward(now, &schedule)?;
+        let reserved_amount = tier_config.reward_over(
+            vault_reward.gem_count,
+            tier_config.required_tenure,
+            denominator,
+        )?;
+
+        if reserved_amount > unearned {
+            let extra = reserved_amount.try_sub(unearned)?;
+
+            if extra > self.funds.pending_amount()? {
+                return Err(ErrorCode::InsufficientFunding.into());
+            }
+
+            self.funds.total_accrued_to_stakers.try_add_assign(extra)?;
+        } else {
+            self.funds
+                .total_accrued_to_stakers
+                .try_sub_assign(unearned.try_sub(reserved_amount)?)?;
+        }
+
+        // 3. start the new tenure
+        vault_reward.reward_tier = tier_config;
+        vault_reward.reserved_amount = reserved_amount;
+        vault_reward.staked_at = now;
+        vault_reward.last_rewards_claimed_at = now;
+
+        Ok(claimed)
+    }
+
+    /// a new tenure on tier_config (tier0 if none is passed) starting now can't end any sooner
+    /// than the current one, otherwise it'd be a way out of the commitment
+    pub fn assert_commits_further(
+        &self,
+        vault_reward: &VaultReward,
+        now: u64,
+        tier_config: Option<TierConfig>,
+    ) -> ProgramResult {
+        let tier = tier_config.unwrap_or(self.fixed_rate.schedule.tier0);
+
+        if now.try_add(tier.required_tenure)? < vault_reward.tenure_expiry()? {
+            return Err(ErrorCode::InvalidTierConfig.into());
+        }
+
+        Ok(())
+    }
+
+    /// starts a new tenure on the same tier once the current one expires. if the reward
+    /// can no longer cover another full tenure, the vault drops down to tier0 instead
+    pub fn renew_tenure(&mut self, vault_reward: &mut VaultReward, now: u64) -> ProgramResult {
+        if matches!(self.reward_type, RewardType::Variable)
+            || vault_reward.reward_tier.required_tenure == 0
+            || now < vault_reward.tenure_expiry()?
+        {
+            return Ok(());
+        }
+
+        let tier = vault_reward.reward_tier;
+        let reserved_amount = tier.reward_over(
+            vault_reward.gem_count,
+            tier.required_tenure,
+            self.fixed_rate.schedule.denominator,
+        )?;
+
+        // the expired tenure was fully earned, so there's nothing left of it to release
+        vault_reward.staked_at = now;
+        vault_reward.last_rewards_claimed_at = now;
+
+        if self.assert_valid_deposit(now, Some(tier)).is_ok()
+            && reserved_amount <= self.funds.pending_amount()?
+        {
+            vault_reward.reserved_amount = reserved_amount;
+            self.funds
+                .total_accrued_to_stakers
+                .try_add_assign(reserved_amount)?;
+
+            msg!("renewed tenure, reserved {}", reserved_amount);
+        } else {
+            vault_reward.reward_tier = self.fixed_rate.schedule.tier0;
+            vault_reward.reserved_amount = 0;
+
+            msg!("couldn't renew tenure, fell back to tier0");
+        }
+
+        Ok(())
+    }
+
+    /// releases the unearned remainder of the vault's reservation back into pending funds.
+    /// whatever was earned stays accrued to stakers, whether it's been claimed yet or not.
+    /// expects the vault to stop earning from here on (ie to be withdrawn)
+    pub fn unreserve_reward(&mut self, vault_reward: &VaultReward, now: u64) -> ProgramResult {
+        // variable rewards are never reserved
+        if matches!(self.reward_type, RewardType::Variable) {
+            return Ok(());
+        }
+
+        // zero once the tenure is over - it's been earned in full
+        let unreserve_amount = vault_reward.unearned_reward(now, &self.fixed_rate.schedule)?;
+
+        msg!("unreserving {}", unreserve_amount);
+
+        self.funds
+            .total_accrued_to_stakers
+            .try_sub_assign(unreserve_amount)?;
+
+        Ok(())
+    }
+
+    /// gives up everything the vault reward still has coming, without computing anything
+    /// new (for emergency withdrawals). what's held for it goes back into pending funds:
+    /// for fixed rates that's the reservation less what's been paid out of it, for variable
+    /// rates what's accrued but unpaid. pass unstaked_at if the gem had begun unstaking -
+    /// the unearned part of the reservation was released then already
+    pub fn forfeit_reward(
+        &mut self,
+        vault_reward: &mut VaultReward,
+        unstaked_at: Option<u64>,
+    ) -> ProgramResult {
+        let release_amount = match self.reward_type {
+            RewardType::Fixed => {
+                let schedule = &self.fixed_rate.schedule;
+
+                let mut held =
+                    vault_reward.unearned_reward(vault_reward.last_rewards_claimed_at, schedule)?;
+                if let Some(unstaked_at) = unstaked_at {
+                    held.try_sub_assign(vault_reward.unearned_reward(unstaked_at, schedule)?)?;
+                }
+
+                vault_reward.reserved_amount = 0;
+                held
+            }
+            RewardType::Variable => {
+                let unpaid = vault_reward
+                    .accrued_reward
+                    .try_sub(vault_reward.paid_out_reward)?;
+
+                vault_reward.accrued_reward = vault_reward.paid_out_reward;
+                unpaid
+            }
+        };
+
+        msg!("forfeiting {}", release_amount);
+
+        self.funds
+            .total_accrued_to_stakers
+            .try_sub_assign(release_amount)
+    }
+
+    /// same as claim_reward, except a pot that can't cover the claim doesn't fail it either -
+    /// whatever it can't cover is owed too, so the vault reward is settled in full either way.
+    /// returns (amount to transfer, amount owed)
+    pub fn claim_reward_or_owe(
+        &mut self,
+        vault_reward: &mut VaultReward,
+        pot_balance: u64,
+        now: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        let (earned, uncovered) = self.settle_earned_reward(vault_reward, now)?;
+
+        let to_claim = std::cmp::min(earned.try_sub(uncovered)?, pot_balance);
+        let owed = earned.try_sub(to_claim)?;
+        self.funds.owed_to_stakers.try_add_assign(owed)?;
+
+        Ok((to_claim, owed))
+    }
+
+    /// marks everything a vault reward earned up to now as paid out. for fixed rewards, the tier0
+    /// part is counted as accrued in full, but pending funds only cover what they can - the rest
+    /// is to be owed rather than taken out of the pot (which holds everyone else's reservations).
+    /// returns (earned, part of it pending funds couldn't cover)
+    fn settle_earned_reward(
+        &mut self,
+        vault_reward: &mut VaultReward,
+        now: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        match self.reward_type {
+            RewardType::Fixed => {
+                let now = self
+                    .times
+                    .fixed_reward_upper_bound(now, vault_reward.tenure_expiry()?);
+                let schedule = self.fixed_rate.schedule;
+
+                let (covered, uncovered) = self.split_unreserved_claim(vault_reward, now)?;
+                self.funds
+                    .total_accrued_to_stakers
+                    .try_add_assign(covered.try_add(uncovered)?)?;
+
+                let earned = vault_reward.claim_rewards(u64::MAX, now, &schedule)?;
+                Ok((earned, uncovered))
+            }
+            RewardType::Variable => Ok((vault_reward.claim_accrued_reward(u64::MAX)?, 0)),
+        }
+    }
+
+    /// anything earned at tier0 rate (post tenure, or staked on tier0 to begin with) since the
+    /// last claim was never reserved, so it has to come out of pending funds as it's claimed.
+    /// returns (part pending funds can cover, part they can't)
+    fn split_unreserved_claim(
+        &self,
+        vault_reward: &VaultReward,
+        now: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        let schedule = &self.fixed_rate.schedule;
+
+        let (_, unreserved_now) = vault_reward.earned_reward_split(now, schedule)?;
+        let (_, unreserved_at_last_claim) =
+            vault_reward.earned_reward_split(vault_reward.last_rewards_claimed_at, schedule)?;
+        let newly_unreserved = unreserved_now.try_sub(unreserved_at_last_claim)?;
+
+        let covered = std::cmp::min(newly_unreserved, self.funds.pending_amount()?);
+
+        Ok((covered, newly_unreserved.try_sub(covered)?))
+    }
+
+    /// skips a vault reward over a period it wasn't earning for
+    pub fn resume_reward(&self, vault_reward: &mut VaultReward, paused_sec: u64) -> ProgramResult {
+        match self.reward_type {
+            // fixed rewards are earned by time since staked_at, so push the whole tenure back
+            RewardType::Fixed => {
+                vault_reward.staked_at.try_add_assign(paused_sec)?;
+                vault_reward
+                    .last_rewards_claimed_at
+                    .try_add_assign(paused_sec)
+            }
+            // and variable ones skip ahead to the current flag position
+            RewardType::Variable => {
+                vault_reward.last_recorded_accrued_reward_per_rarity_point =
+                    self.variable_rate.accrued_reward_per_rarity_point;
+                Ok(())
+            }
+        }
+    }
+
+    /// unlike claim_reward_or_owe, fails if the pot can't cover what's been earned. only tier0
+    /// accrual that outran pending funds is owed, so a claim never fails on that.
+    /// returns (amount to transfer out of the pot to the vault owner, amount owed to the vault)
+    pub fn claim_reward(
+        &mut self,
+        vault_reward: &mut VaultReward,
+        pot_balance: u64,
+        now: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        let (earned, uncovered) = self.settle_earned_reward(vault_reward, now)?;
+
+        let to_claim = earned.try_sub(uncovered)?;
+        if to_claim > pot_balance {
+            return Err(ErrorCode::InsufficientFunding.into());
+        }
+
+        self.funds.owed_to_stakers.try_add_assign(uncovered)?;
+
+        Ok((to_claim, uncovered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number128::Number128;
+
+    fn time_tracker(reward_end_ts: u64) -> TimeTracker {
+        TimeTracker {
+            duration_sec: 0,
+            reward_end_ts,
+            lock_end_ts: 0,
+        }
+    }
+
+    fn fixed_reward(total_funded: u64) -> FarmReward {
+        FarmReward {
+            reward_mint: Pubkey::default(),
+            reward_pot: Pubkey::default(),
+            reward_type: RewardType::Fixed,
+            fixed_rate: FixedRateReward::new(FixedRateSchedule {
+                tier0: TierConfig::default(),
+                tier1: Some(TierConfig {
+                    reward_rate: 10,
+                    required_tenure: 100,
+                }),
+                tier2: Some(TierConfig {
+                    reward_rate: 20,
+                    required_tenure: 200,
+                }),
+                tier3: None,
+                denominator: 1,
+            }),
+            variable_rate: VariableRateReward {
+                reward_rate: Number128::ZERO,
+                reward_last_updated_ts: 0,
+                accrued_reward_per_rarity_point: Number128::ZERO,
+            },
+            funds: FundsTracker {
+                total_funded,
+                total_refunded: 0,
+                total_accrued_to_stakers: 0,
+                owed_to_stakers: 0,
+            },
+            times: time_tracker(0),
+        }
+    }
+
+    #[test]
+    fn test_upgrade_tier() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+        let tier2 = reward.fixed_rate.schedule.tier2.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+        assert_eq!(reward.funds.total_accrued_to_stakers, 1000);
+
+        // half way through tier1: 500 earned, 500 unearned goes towards tier2's 4000
+        let claimed = reward
+            .upgrade_tier(&mut vault_reward, 50, tier2, u64::MAX)
+            .unwrap();
+
+        assert_eq!(claimed, (500, 0));
+        assert_eq!(vault_reward.reserved_amount, 4000);
+        assert_eq!(vault_reward.staked_at, 50);
+        assert_eq!(reward.funds.total_accrued_to_stakers, 4500);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 5500);
+    }
+
+    #[test]
+    fn test_upgrade_tier_cant_shorten_commitment() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+        let tier2 = reward.fixed_rate.schedule.tier2.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier2))
+            .unwrap();
+
+        // tier2 expires at 200, tier1 from now would expire at 110
+        assert!(reward
+            .upgrade_tier(&mut vault_reward, 10, tier1, u64::MAX)
+            .is_err());
+    }
+
+    #[test]
+    fn test_top_up_cant_shorten_commitment() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+        let tier2 = reward.fixed_rate.schedule.tier2.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+
+        // restaking the whole gem on tier0 mid tenure would end it early
+        assert!(reward
+            .assert_commits_further(&vault_reward, 50, None)
+            .is_err());
+        assert!(reward
+            .assert_commits_further(&vault_reward, 50, Some(tier1))
+            .is_ok());
+        assert!(reward
+            .assert_commits_further(&vault_reward, 50, Some(tier2))
+            .is_ok());
+
+        // once the tenure's up any tier goes
+        assert!(reward
+            .assert_commits_further(&vault_reward, 100, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_renew_tenure() {
+        let mut reward = fixed_reward(2000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+
+        // still running, nothing happens
+        reward.renew_tenure(&mut vault_reward, 50).unwrap();
+        assert_eq!(vault_reward.staked_at, 0);
+
+        // expired, enough funding left for another 1000
+        reward
+            .claim_reward(&mut vault_reward, u64::MAX, 120)
+            .unwrap();
+        reward.renew_tenure(&mut vault_reward, 120).unwrap();
+        assert_eq!(vault_reward.staked_at, 120);
+        assert_eq!(vault_reward.reserved_amount, 1000);
+        assert_eq!(reward.funds.total_accrued_to_stakers, 2000);
+
+        // expired again, but only 500 pending - not enough for another tenure, drops to tier0
+        reward.funds.total_funded = 2500;
+        reward
+            .claim_reward(&mut vault_reward, u64::MAX, 230)
+            .unwrap();
+        reward.renew_tenure(&mut vault_reward, 230).unwrap();
+        assert_eq!(vault_reward.reward_tier.required_tenure, 0);
+        assert_eq!(vault_reward.reserved_amount, 0);
+        assert_eq!(reward.funds.total_accrued_to_stakers, 2000);
+    }
+
+    #[test]
+    fn test_tier0_accrual_after_expiry_comes_out_of_pending() {
+        let mut reward = fixed_reward(1100);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+        reward.fixed_rate.schedule.tier0.reward_rate = 1;
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+
+        // 1000 reserved on tier1, then 50 at tier0 rate which wasn't reserved
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 150)
+            .unwrap();
+        assert_eq!(claimed, (1050, 0));
+        assert_eq!(reward.funds.total_accrued_to_stakers, 1050);
+
+        // only 50 left pending to cover 60 more at tier0 - the claim still goes through,
+        // and the other 10 are owed
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 210)
+            .unwrap();
+        assert_eq!(claimed, (50, 10));
+        assert_eq!(vault_reward.paid_out_reward, 1110);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_unreserve_on_early_exit() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+
+        // 300 of the 1000 earned, the other 700 go back to pending
+        reward.unreserve_reward(&vault_reward, 30).unwrap();
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 30)
+            .unwrap();
+
+        assert_eq!(claimed, (300, 0));
+        assert_eq!(reward.funds.total_accrued_to_stakers, 300);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 9700);
+    }
+
+    #[test]
+    fn test_unreserve_on_time_exit() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+
+        // tenure is over, the reservation was earned in full - nothing to release
+        reward.unreserve_reward(&vault_reward, 150).unwrap();
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 150)
+            .unwrap();
+
+        assert_eq!(claimed, (1000, 0));
+        assert_eq!(reward.funds.total_accrued_to_stakers, 1000);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 9000);
+    }
+
+    #[test]
+    fn test_unreserve_after_partial_claims() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+
+        let mut total_claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 20)
+            .unwrap()
+            .0;
+        total_claimed += reward
+            .claim_reward(&mut vault_reward, u64::MAX, 50)
+            .unwrap()
+            .0;
+
+        // earlier claims don't change what's left unearned
+        reward.unreserve_reward(&vault_reward, 70).unwrap();
+        total_claimed += reward
+            .claim_reward(&mut vault_reward, u64::MAX, 70)
+            .unwrap()
+            .0;
+
+        assert_eq!(total_claimed, 700);
+        assert_eq!(reward.funds.total_accrued_to_stakers, 700);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 9300);
+    }
+
+    #[test]
+    fn test_reservation_scales_with_gem_count() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        // 3 units on tier1 reserve 3x the 1000
+        reward
+            .reserve_reward(&mut vault_reward, 0, 3, Some(tier1))
+            .unwrap();
+        assert_eq!(vault_reward.reserved_amount, 3000);
+        assert_eq!(reward.funds.total_accrued_to_stakers, 3000);
+
+        // and earn 3x as fast, leaving 3x as much to release on an early exit
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 50)
+            .unwrap();
+        reward.unreserve_reward(&vault_reward, 50).unwrap();
+
+        assert_eq!(claimed, (1500, 0));
+        assert_eq!(reward.funds.total_accrued_to_stakers, 1500);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 8500);
+    }
+
+    #[test]
+    fn test_claim_reward_or_owe() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+
+        // a plain claim can't go through on a short pot
+        let mut short_claim = vault_reward;
+        assert!(reward.claim_reward(&mut short_claim, 300, 50).is_err());
+
+        // this one pays what's there and owes the rest
+        let (to_claim, owed) = reward
+            .claim_reward_or_owe(&mut vault_reward, 300, 50)
+            .unwrap();
+        assert_eq!((to_claim, owed), (300, 200));
+        assert_eq!(vault_reward.paid_out_reward, 500);
+
+        // nothing's owed when the pot can cover it
+        let (to_claim, owed) = reward
+            .claim_reward_or_owe(&mut vault_reward, u64::MAX, 100)
+            .unwrap();
+        assert_eq!((to_claim, owed), (500, 0));
+    }
+
+    #[test]
+    fn test_claim_reward_or_owe_past_pending() {
+        let mut reward = fixed_reward(1100);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+        reward.fixed_rate.schedule.tier0.reward_rate = 1;
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 150)
+            .unwrap();
+        assert_eq!(claimed, (1050, 0));
+
+        // 60 more at tier0 with only 50 pending: pending covers 50, the other 10 are owed.
+        // the pot could pay more, but that's other stakers' reservations
+        let (to_claim, owed) = reward
+            .claim_reward_or_owe(&mut vault_reward, u64::MAX, 210)
+            .unwrap();
+        assert_eq!((to_claim, owed), (50, 10));
+        assert_eq!(vault_reward.paid_out_reward, 1110);
+        assert_eq!(reward.funds.total_accrued_to_stakers, 1110);
+        assert_eq!(reward.funds.owed_to_stakers, 10);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 0);
+
+        // and a short pot on top of that is owed as well
+        let (to_claim, owed) = reward
+            .claim_reward_or_owe(&mut vault_reward, 0, 220)
+            .unwrap();
+        assert_eq!((to_claim, owed), (0, 10));
+        assert_eq!(reward.funds.owed_to_stakers, 20);
+    }
+
+    #[test]
+    fn test_owed_reward_leaves_other_reservations_alone() {
+        let mut reward = fixed_reward(2000);
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+        reward.fixed_rate.schedule.tier0.reward_rate = 1;
+
+        // two gems on tier1 reserve everything funded between them, the second one a bit later
+        let mut leaving = VaultReward::default();
+        let mut staying = VaultReward::default();
+        reward
+            .reserve_reward(&mut leaving, 0, 1, Some(tier1))
+            .unwrap();
+        reward
+            .reserve_reward(&mut staying, 50, 1, Some(tier1))
+            .unwrap();
+        let mut pot = 2000;
+
+        // one leaves 50s after its tenure, with 50 at tier0 rate nothing was funded for
+        let (to_claim, owed) = reward.claim_reward_or_owe(&mut leaving, pot, 150).unwrap();
+        assert_eq!((to_claim, owed), (1000, 50));
+        pot -= to_claim;
+        assert_eq!(reward.funds.total_accrued_to_stakers, 2050);
+        assert_eq!(reward.funds.owed_to_stakers, 50);
+
+        // the 1000 left in the pot are the other gem's reservation - none of it pays the IOU,
+        // and none of it can be refunded either
+        assert_eq!(reward.funds.pay_owed(50, pot).unwrap(), 0);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 0);
+
+        // a top up goes towards the IOU first
+        reward.fund_reward(150, 30, None, 0).unwrap();
+        pot += 30;
+        assert_eq!(reward.funds.pending_amount().unwrap(), 0);
+        assert_eq!(reward.funds.pay_owed(50, pot).unwrap(), 30);
+        pot -= 30;
+
+        reward.fund_reward(150, 100, None, 0).unwrap();
+        pot += 100;
+        assert_eq!(reward.funds.pending_amount().unwrap(), 80);
+        assert_eq!(reward.funds.pay_owed(20, pot).unwrap(), 20);
+        pot -= 20;
+        assert_eq!(reward.funds.owed_to_stakers, 0);
+
+        // and the other gem still gets its full reservation once its tenure is up,
+        // leaving only what's pending in the pot
+        let (to_claim, owed) = reward.claim_reward(&mut staying, pot, 150).unwrap();
+        assert_eq!((to_claim, owed), (1000, 0));
+        assert_eq!(pot - to_claim, reward.funds.pending_amount().unwrap());
+    }
+
+    #[test]
+    fn test_forfeit_reward() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 30)
+            .unwrap();
+        assert_eq!(claimed, (300, 0));
+
+        // the 300 paid out stays accrued, the other 700 (earned or not) go back to pending
+        reward.forfeit_reward(&mut vault_reward, None).unwrap();
+        assert_eq!(vault_reward.reserved_amount, 0);
+        assert_eq!(reward.funds.total_accrued_to_stakers, 300);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 9700);
+    }
+
+    #[test]
+    fn test_forfeit_reward_while_unstaking() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+        reward
+            .claim_reward(&mut vault_reward, u64::MAX, 20)
+            .unwrap();
+
+        // began unstaking at 50, releasing the 500 that would've been earned after
+        reward.unreserve_reward(&vault_reward, 50).unwrap();
+        assert_eq!(reward.funds.total_accrued_to_stakers, 500);
+
+        // only the 300 earned between the claim and unstaking is left to release
+        reward.forfeit_reward(&mut vault_reward, Some(50)).unwrap();
+        assert_eq!(reward.funds.total_accrued_to_stakers, 200);
+        assert_eq!(reward.funds.pending_amount().unwrap(), 9800);
+    }
+
+    #[test]
+    fn test_resume_fixed_reward() {
+        let mut reward = fixed_reward(10_000);
+        let mut vault_reward = VaultReward::default();
+        let tier1 = reward.fixed_rate.schedule.tier1.unwrap();
+
+        reward
+            .reserve_reward(&mut vault_reward, 0, 1, Some(tier1))
+            .unwrap();
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 20)
+            .unwrap();
+        assert_eq!(claimed, (200, 0));
+
+        // frozen at 40 for 100s: 200 more earned before, 200 after, none in between
+        reward.resume_reward(&mut vault_reward, 100).unwrap();
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 160)
+            .unwrap();
+        assert_eq!(claimed, (400, 0));
+
+        // the tenure is pushed back too, so the full reservation still gets earned
+        assert_eq!(vault_reward.tenure_expiry().unwrap(), 200);
+        let claimed = reward
+            .claim_reward(&mut vault_reward, u64::MAX, 200)
+            .unwrap();
+        assert_eq!(claimed, (400, 0));
+        assert_eq!(vault_reward.paid_out_reward, 1000);
+    }
+
+    #[test]
+    fn test_fund_reward_top_up() {
+        let mut fixed = fixed_reward(0);
+        let mut variable = FarmReward {
+            reward_type: RewardType::Variable,
+            ..fixed_reward(0)
+        };
+
+        for reward in [&mut fixed, &mut variable] {
+            reward.fund_reward(100, 1000, Some(100), 0).unwrap();
+            assert_eq!(reward.times.reward_end_ts, 200);
+
+            // a short top up still pushes the end out, by its full duration
+            reward.fund_reward(150, 1000, Some(10), 0).unwrap();
+            assert_eq!(reward.times.reward_end_ts, 210);
+            assert_eq!(reward.times.duration_sec, 110);
+
+            // once a round has ended, the next one starts now
+            reward.fund_reward(300, 1000, Some(10), 0).unwrap();
+            assert_eq!(reward.times.reward_end_ts, 310);
+        }
+    }
+
+    #[test]
+    fn test_lock_reward() {
+        // nothing funded yet, nothing to lock
+        let mut reward = fixed_reward(0);
+        assert!(reward.lock_reward(100).is_err());
+
+        // funded without a duration, so it never ends and neither does the lock
+        let mut reward = fixed_reward(1000);
+        reward.lock_reward(100).unwrap();
+        assert_eq!(reward.times.lock_end_ts, u64::MAX);
+        assert!(reward.cancel_reward(1_000_000, 0).is_err());
+
+        // with a duration, locked until it ends
+        let mut reward = fixed_reward(1000);
+        reward.times.extend_reward(100, 50).unwrap();
+        reward.lock_reward(120).unwrap();
+        assert_eq!(reward.times.lock_end_ts, 150);
+        assert!(reward.cancel_reward(149, 0).is_err());
+        assert_eq!(reward.cancel_reward(150, 0).unwrap(), 1000);
+        assert!(reward.lock_reward(150).is_err());
+    }
+
+    #[test]
+    fn test_extend_reward() {
+        // first round starts now
+        let mut times = time_tracker(0);
+        times.extend_reward(100, 50).unwrap();
+        assert_eq!(times.reward_end_ts, 150);
+
+        // a round funded before the previous one ends is appended to it
+        times.extend_reward(120, 50).unwrap();
+        assert_eq!(times.reward_end_ts, 200);
+        assert_eq!(times.duration_sec, 100);
+
+        // a round funded after the previous one ended starts now
+        times.extend_reward(300, 10).unwrap();
+        assert_eq!(times.reward_end_ts, 310);
+    }
+
+    #[test]
+    fn test_fixed_reward_upper_bound() {
+        // never funded with a duration = never ends
+        assert_eq!(time_tracker(0).fixed_reward_upper_bound(1000, 0), 1000);
+
+        // capped at reward end
+        assert_eq!(time_tracker(500).fixed_reward_upper_bound(1000, 0), 500);
+        assert_eq!(time_tracker(500).fixed_reward_upper_bound(400, 0), 400);
+
+        // unless the tenure was promised past it
+        assert_eq!(time_tracker(500).fixed_reward_upper_bound(1000, 700), 700);
+    }
+}
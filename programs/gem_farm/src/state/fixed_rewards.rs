@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use gem_common::errors::ErrorCode;
+use gem_common::{errors::ErrorCode, *};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
@@ -19,12 +19,31 @@ pub struct TierConfig {
     // we'll store reward_rate as 10, and denominator as 86,400
     // next, say we wanted this reward to come with a staking period of 60 days.
     // we'll store required_tenure as 60*86400 = 5,184,000.
-    // The total amount reserved from the farm funds will be (reward_rate / denominator) * required_tenure
+    // The total amount reserved from the farm funds will be (reward_rate * required_tenure) / denominator
     // which will equal 600, resulting in the initial 60 days staking * 10 per day.
     /// lock duration to earn the above reward rate
     pub required_tenure: u64, // we'll save this value in seconds. so 60 days will be 60 * 86400 stored here.
 }
 
+impl TierConfig {
+    /// reward for `gem_count` units staked over `duration`. the rate is per unit, so fungible
+    /// and semi-fungible deposits earn in proportion to the amount staked.
+    /// multiplies before dividing, otherwise slow rates like the 10/86,400 per sec above
+    /// would truncate to 0 before ever being multiplied
+    pub fn reward_over(
+        &self,
+        gem_count: u64,
+        duration: u64,
+        denominator: u64,
+    ) -> Result<u64, ProgramError> {
+        u128::from(self.reward_rate)
+            .try_mul(u128::from(duration))?
+            .try_mul(u128::from(gem_count))?
+            .try_div(u128::from(denominator))?
+            .try_cast()
+    }
+}
+
 impl Default for TierConfig {
     fn default() -> Self {
         Self {
@@ -155,3 +174,79 @@ impl FixedRateReward {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reward_over_documented_example() {
+        let tier = TierConfig {
+            reward_rate: 10,
+            required_tenure: 60 * 86_400,
+        };
+
+        assert_eq!(
+            tier.reward_over(1, tier.required_tenure, 86_400).unwrap(),
+            600
+        );
+    }
+
+    #[test]
+    fn test_reward_over_rounds_down() {
+        let tier = TierConfig {
+            reward_rate: 10,
+            required_tenure: 0,
+        };
+
+        // 10 * 100 / 3 = 333.33
+        assert_eq!(tier.reward_over(1, 100, 3).unwrap(), 333);
+        // 10 * 1 / 86,400 = 0.0001
+        assert_eq!(tier.reward_over(1, 1, 86_400).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_reward_over_errors_instead_of_overflowing() {
+        let tier = TierConfig {
+            reward_rate: u64::MAX,
+            required_tenure: 0,
+        };
+
+        // the u128 product itself overflows
+        assert!(tier.reward_over(u64::MAX, u64::MAX, 1).is_err());
+        // fits in u128, but not the u64 result
+        assert!(tier.reward_over(2, 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_reward_over_no_intermediate_overflow() {
+        let tier = TierConfig {
+            reward_rate: u64::MAX,
+            required_tenure: 0,
+        };
+
+        // rate * duration overflows u64, but the result after dividing fits
+        assert_eq!(tier.reward_over(1, 1000, 1000).unwrap(), u64::MAX);
+
+        // results that don't fit error out instead of wrapping
+        assert!(tier.reward_over(1, 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_reward_over_scales_with_gem_count() {
+        let tier = TierConfig {
+            reward_rate: 10,
+            required_tenure: 0,
+        };
+
+        // 1,000 units staked for a day earn as much as 1 unit for 1,000 days
+        assert_eq!(tier.reward_over(1000, 86_400, 86_400).unwrap(), 10_000);
+        assert_eq!(
+            tier.reward_over(1000, 86_400, 86_400).unwrap(),
+            tier.reward_over(1, 1000 * 86_400, 86_400).unwrap()
+        );
+
+        // nothing staked, nothing earned
+        assert_eq!(tier.reward_over(0, 86_400, 86_400).unwrap(), 0);
+    }
+}
@@ -8,7 +8,8 @@ pub struct GemDepositReceipt {
     /// each gem gox sits inside a single vault
     pub vault: Pubkey,
 
-    /// the token account that actually holds the deposited gem(s)
+    /// the token account that actually holds the deposited gem(s) - the owner's own token
+    /// account for a gem staked in place
     pub gem_box_address: Pubkey,
 
     /// the following is really stored for convenience, so we don't have to fetch gem account separately
@@ -4,6 +4,9 @@ pub mod fixed_rewards;
 pub mod variable_rewards;
 pub mod vault;
 pub mod gem_deposit_receipt;
+pub mod staked_gem;
+pub mod whitelist_proof;
+pub mod denylist_proof;
 
 pub use authorization_proof::*;
 pub use farm::*;
@@ -11,3 +14,6 @@ pub use fixed_rewards::*;
 pub use variable_rewards::*;
 pub use vault::*;
 pub use gem_deposit_receipt::*;
+pub use staked_gem::*;
+pub use whitelist_proof::*;
+pub use denylist_proof::*;
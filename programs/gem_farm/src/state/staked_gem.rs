@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+use gem_common::{errors::ErrorCode, *};
+
+use crate::state::*;
+
+/// one per gem staked in a vault, a vault can hold any number of them.
+/// each gem keeps its own tenure and reward records, the vault's rewards are the sum of them
+#[repr(C)]
+#[account]
+pub struct StakedGem {
+    pub vault: Pubkey,
+
+    pub gem_mint: Pubkey,
+
+    /// the token account actually holding the gem - owned by the vault authority,
+    /// or the owner's own token account if the gem is frozen in place
+    pub gem_box: Pubkey,
+
+    /// staked without custody: the gem never left the owner's wallet, the vault authority
+    /// is its delegate and the token account is frozen until unstake_in_place
+    pub frozen_in_place: bool,
+
+    /// amount of the mint staked - 1 for an NFT, anything for fungible / semi-fungible tokens.
+    /// depositing more of a mint that's already staked tops this up, see Farm::top_up_gem
+    pub gem_count: u64,
+
+    /// this gem's share of the vault's rarity_points_staked
+    pub rarity_points: u64,
+
+    /// set on deposit, unstaking can't begin before this
+    pub min_staking_ends_ts: u64,
+
+    /// set by begin_unstake - the gem stops earning at unstaked_at
+    /// and can be withdrawn once the cooldown ends
+    pub unstaking: bool,
+
+    pub unstaked_at: u64,
+
+    pub cooldown_ends_ts: u64,
+
+    // ----------------- rewards
+    pub reward_a: VaultReward,
+
+    pub reward_b: VaultReward,
+}
+
+impl StakedGem {
+    pub fn attempting_to_break_bank(&self, now: u64) -> Result<bool, ProgramError> {
+        // both rewards have to be past their tenure, otherwise one of them is being broken
+        if now > self.reward_a.tenure_expiry()? && now > self.reward_b.tenure_expiry()? {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// the gem stops earning the moment unstaking begins
+    pub fn accrual_upper_bound(&self, now: u64) -> u64 {
+        if self.unstaking {
+            std::cmp::min(now, self.unstaked_at)
+        } else {
+            now
+        }
+    }
+
+    pub fn begin_unstake(&mut self, now: u64, cooldown_sec: u64) -> ProgramResult {
+        if self.unstaking {
+            return Err(ErrorCode::InvalidParameter.into());
+        }
+
+        if now < self.min_staking_ends_ts {
+            return Err(ErrorCode::MinStakingNotPassed.into());
+        }
+
+        self.unstaking = true;
+        self.unstaked_at = now;
+        self.cooldown_ends_ts = now.try_add(cooldown_sec)?;
+
+        Ok(())
+    }
+
+    pub fn assert_cooldown_passed(&self, now: u64) -> ProgramResult {
+        if !self.unstaking {
+            return Err(ErrorCode::UnstakeNotStarted.into());
+        }
+
+        if now < self.cooldown_ends_ts {
+            return Err(ErrorCode::CooldownNotPassed.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staked_gem() -> StakedGem {
+        StakedGem {
+            vault: Pubkey::default(),
+            gem_mint: Pubkey::default(),
+            gem_box: Pubkey::default(),
+            frozen_in_place: false,
+            gem_count: 1,
+            rarity_points: 1,
+            min_staking_ends_ts: 100,
+            unstaking: false,
+            unstaked_at: 0,
+            cooldown_ends_ts: 0,
+            reward_a: VaultReward::default(),
+            reward_b: VaultReward::default(),
+        }
+    }
+
+    #[test]
+    fn test_cant_unstake_before_min_staking() {
+        let mut gem = staked_gem();
+
+        assert!(gem.begin_unstake(99, 50).is_err());
+        assert!(!gem.unstaking);
+
+        gem.begin_unstake(100, 50).unwrap();
+        assert_eq!(gem.cooldown_ends_ts, 150);
+
+        // can only begin once
+        assert!(gem.begin_unstake(120, 50).is_err());
+    }
+
+    #[test]
+    fn test_withdraw_needs_cooldown() {
+        let mut gem = staked_gem();
+
+        // never began unstaking
+        assert!(gem.assert_cooldown_passed(1000).is_err());
+
+        gem.begin_unstake(100, 50).unwrap();
+        assert!(gem.assert_cooldown_passed(149).is_err());
+        gem.assert_cooldown_passed(150).unwrap();
+    }
+
+    #[test]
+    fn test_accrual_stops_when_unstaking_begins() {
+        let mut gem = staked_gem();
+        assert_eq!(gem.accrual_upper_bound(120), 120);
+
+        gem.begin_unstake(120, 50).unwrap();
+        assert_eq!(gem.accrual_upper_bound(110), 110);
+        assert_eq!(gem.accrual_upper_bound(160), 120);
+    }
+}
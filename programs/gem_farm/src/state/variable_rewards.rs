@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
+use gem_common::*;
 
-use crate::{number128::Number128};
+use crate::{number128::Number128, state::*};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, AnchorSerialize, AnchorDeserialize, PartialEq)]
@@ -31,4 +32,305 @@ pub struct VariableRateReward {
 }
 
 impl VariableRateReward {
+    /// everything that hasn't yet accrued to stakers (including any leftovers from
+    /// previous rounds during which nobody was staked) gets spread over what's left of the
+    /// current round plus the new duration
+    pub fn fund_reward(
+        &mut self,
+        now: u64,
+        times: &mut TimeTracker,
+        funds: &mut FundsTracker,
+        new_config: VariableRateConfig,
+    ) -> ProgramResult {
+        let VariableRateConfig {
+            amount,
+            duration_sec,
+        } = new_config;
+
+        funds.total_funded.try_add_assign(amount)?;
+
+        // same as fixed rewards, the new round is appended to whatever's left of the current one
+        times.extend_reward(now, duration_sec)?;
+
+        self.reward_rate =
+            Number128::from_ratio(funds.pending_amount()?, times.reward_end_ts.try_sub(now)?)?;
+
+        self.reward_last_updated_ts = now;
+
+        msg!("variable reward rate set to {} tokens/s", self.reward_rate);
+        Ok(())
+    }
+
+    /// stops any further accrual - whatever was still pending is being refunded
+    pub fn cancel_reward(&mut self, now: u64, times: &mut TimeTracker) {
+        times.reward_end_ts = times.reward_upper_bound(now);
+
+        self.reward_rate = Number128::ZERO;
+        self.reward_last_updated_ts = times.reward_end_ts;
+    }
+
+    /// moves the flag forward up to now (capped at reward end), and if a vault is passed
+    /// in, settles whatever the vault earned since its last recorded flag position
+    pub fn update_accrued_reward(
+        &mut self,
+        now: u64,
+        times: &TimeTracker,
+        funds: &mut FundsTracker,
+        farm_rarity_points_staked: u64,
+        vault_reward: Option<(&mut VaultReward, u64)>,
+    ) -> ProgramResult {
+        let reward_upper_bound = times.reward_upper_bound(now);
+
+        let newly_accrued_reward_per_rarity_point = self
+            .newly_accrued_reward_per_rarity_point(farm_rarity_points_staked, reward_upper_bound)?;
+
+        self.accrued_reward_per_rarity_point
+            .try_add_assign(newly_accrued_reward_per_rarity_point)?;
+
+        funds.total_accrued_to_stakers.try_add_assign(
+            newly_accrued_reward_per_rarity_point
+                .try_mul_int(farm_rarity_points_staked)?
+                .as_u64(0)?,
+        )?;
+
+        if let Some((vault_reward, vault_rarity_points_staked)) = vault_reward {
+            let newly_accrued_to_vault = self
+                .accrued_reward_per_rarity_point
+                .try_sub(vault_reward.last_recorded_accrued_reward_per_rarity_point)?
+                .try_mul_int(vault_rarity_points_staked)?;
+
+            vault_reward
+                .accrued_reward
+                .try_add_assign(newly_accrued_to_vault.as_u64(0)?)?;
+            vault_reward.last_recorded_accrued_reward_per_rarity_point =
+                self.accrued_reward_per_rarity_point;
+        }
+
+        self.reward_last_updated_ts = reward_upper_bound;
+
+        Ok(())
+    }
+
+    fn newly_accrued_reward_per_rarity_point(
+        &self,
+        farm_rarity_points_staked: u64,
+        reward_upper_bound: u64,
+    ) -> Result<Number128, ProgramError> {
+        // nobody staked = nothing accrues, the funds stay pending until the next funding round
+        if farm_rarity_points_staked == 0 || reward_upper_bound <= self.reward_last_updated_ts {
+            return Ok(Number128::ZERO);
+        }
+
+        let time_since_last_calc = reward_upper_bound.try_sub(self.reward_last_updated_ts)?;
+
+        // multiply before dividing, and only by plain integers - the rate is already scaled
+        self.reward_rate
+            .try_mul_int(time_since_last_calc)?
+            .try_div_int(farm_rarity_points_staked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn funded_reward(amount: u64, duration_sec: u64) -> (VariableRateReward, TimeTracker, FundsTracker) {
+        let mut reward = VariableRateReward {
+            reward_rate: Number128::ZERO,
+            reward_last_updated_ts: 0,
+            accrued_reward_per_rarity_point: Number128::ZERO,
+        };
+        let mut times = TimeTracker {
+            duration_sec: 0,
+            reward_end_ts: 0,
+            lock_end_ts: 0,
+        };
+        let mut funds = FundsTracker {
+            total_funded: 0,
+            total_refunded: 0,
+            total_accrued_to_stakers: 0,
+            owed_to_stakers: 0,
+        };
+
+        reward
+            .fund_reward(
+                100,
+                &mut times,
+                &mut funds,
+                VariableRateConfig {
+                    amount,
+                    duration_sec,
+                },
+            )
+            .unwrap();
+
+        (reward, times, funds)
+    }
+
+    #[test]
+    fn test_fund_sets_rate_and_times() {
+        let (reward, times, funds) = funded_reward(10_000, 100);
+
+        assert_eq!(reward.reward_rate, Number128::from(100_u64));
+        assert_eq!(reward.reward_last_updated_ts, 100);
+        assert_eq!(times.duration_sec, 100);
+        assert_eq!(times.reward_end_ts, 200);
+        assert_eq!(funds.total_funded, 10_000);
+    }
+
+    #[test]
+    fn test_accrual_split_between_vaults() {
+        let (mut reward, times, mut funds) = funded_reward(10_000, 100);
+        let mut vault_reward = VaultReward::default();
+
+        // 2 points staked in total, this vault has 1 of them
+        reward
+            .update_accrued_reward(110, &times, &mut funds, 2, Some((&mut vault_reward, 1)))
+            .unwrap();
+
+        assert_eq!(reward.accrued_reward_per_rarity_point, Number128::from(500_u64));
+        assert_eq!(funds.total_accrued_to_stakers, 1000);
+        assert_eq!(vault_reward.accrued_reward, 500);
+
+        // a second update at the same moment doesn't double count
+        reward
+            .update_accrued_reward(110, &times, &mut funds, 2, Some((&mut vault_reward, 1)))
+            .unwrap();
+        assert_eq!(vault_reward.accrued_reward, 500);
+    }
+
+    #[test]
+    fn test_accrual_capped_at_reward_end() {
+        let (mut reward, times, mut funds) = funded_reward(10_000, 100);
+        let mut vault_reward = VaultReward::default();
+
+        reward
+            .update_accrued_reward(1000, &times, &mut funds, 1, Some((&mut vault_reward, 1)))
+            .unwrap();
+
+        assert_eq!(vault_reward.accrued_reward, 10_000);
+        assert_eq!(funds.pending_amount().unwrap(), 0);
+        assert_eq!(reward.reward_last_updated_ts, 200);
+    }
+
+    #[test]
+    fn test_refund_rolls_over_unaccrued() {
+        let (mut reward, mut times, mut funds) = funded_reward(10_000, 100);
+
+        // half the duration passes with 1 point staked
+        reward
+            .update_accrued_reward(150, &times, &mut funds, 1, None)
+            .unwrap();
+        assert_eq!(funds.total_accrued_to_stakers, 5000);
+
+        // the remaining 5000 + another 5000 get spread over the new duration
+        reward
+            .fund_reward(
+                150,
+                &mut times,
+                &mut funds,
+                VariableRateConfig {
+                    amount: 5000,
+                    duration_sec: 50,
+                },
+            )
+            .unwrap();
+
+        // 50s were left of the first round, plus the 50 just funded
+        assert_eq!(reward.reward_rate, Number128::from(100_u64));
+        assert_eq!(times.reward_end_ts, 250);
+    }
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn test_realistic_amounts() {
+        // 5M tokens at 9 decimals over 90 days - way past what try_div could handle
+        let amount = 5_000_000_000_000_000;
+        let (mut reward, times, mut funds) = funded_reward(amount, 90 * DAY);
+        let mut vault_reward = VaultReward::default();
+
+        // a third of the way through, 3 points staked in total, this vault has 1 of them
+        reward
+            .update_accrued_reward(
+                100 + 30 * DAY,
+                &times,
+                &mut funds,
+                3,
+                Some((&mut vault_reward, 1)),
+            )
+            .unwrap();
+
+        let expected = amount / 9;
+        assert!(expected - vault_reward.accrued_reward <= 1);
+        assert!(amount / 3 - funds.total_accrued_to_stakers <= 1);
+
+        // by the end, everything's accrued (give or take rounding down)
+        reward
+            .update_accrued_reward(
+                100 + 90 * DAY,
+                &times,
+                &mut funds,
+                3,
+                Some((&mut vault_reward, 1)),
+            )
+            .unwrap();
+
+        assert!(amount / 3 - vault_reward.accrued_reward <= 1);
+        assert!(funds.pending_amount().unwrap() <= 3);
+    }
+
+    #[test]
+    fn test_two_funding_rounds() {
+        let amount = 1_000_000_000_000;
+        let (mut reward, mut times, mut funds) = funded_reward(amount, 60 * DAY);
+
+        // half way through, half has accrued
+        let now = 100 + 30 * DAY;
+        reward
+            .update_accrued_reward(now, &times, &mut funds, 1, None)
+            .unwrap();
+        assert!(amount / 2 - funds.total_accrued_to_stakers <= 1);
+
+        // a top up is appended to what's left of the round, and spread over all of it
+        reward
+            .fund_reward(
+                now,
+                &mut times,
+                &mut funds,
+                VariableRateConfig {
+                    amount,
+                    duration_sec: 10 * DAY,
+                },
+            )
+            .unwrap();
+        assert_eq!(times.reward_end_ts, 100 + 70 * DAY);
+        assert_eq!(times.duration_sec, 70 * DAY);
+        assert_eq!(
+            reward.reward_rate,
+            Number128::from_ratio(funds.pending_amount().unwrap(), 40 * DAY).unwrap()
+        );
+
+        // however long it is
+        reward
+            .fund_reward(
+                now,
+                &mut times,
+                &mut funds,
+                VariableRateConfig {
+                    amount,
+                    duration_sec: 90 * DAY,
+                },
+            )
+            .unwrap();
+        assert_eq!(times.reward_end_ts, 100 + 160 * DAY);
+        assert_eq!(times.duration_sec, 160 * DAY);
+
+        // and the whole lot accrues by the new end
+        reward
+            .update_accrued_reward(100 + 160 * DAY, &times, &mut funds, 1, None)
+            .unwrap();
+        assert!(funds.pending_amount().unwrap() <= 1);
+        assert!(3 * amount - funds.total_accrued_to_stakers <= 1);
+    }
 }
@@ -1,10 +1,10 @@
 use anchor_lang::prelude::*;
 use gem_common::{errors::ErrorCode, *};
 
-use crate::state::*;
+use crate::{number128::Number128, state::*};
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
+#[derive(Debug, Default, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct VaultReward {
     pub paid_out_reward: u64,
 
@@ -15,69 +15,123 @@ pub struct VaultReward {
     pub reward_tier: TierConfig,
 
     pub last_rewards_claimed_at: u64,
-}
 
-impl VaultReward {
-    pub fn outstanding_reward(&self, now: u64, denominator: u64) -> Result<u64, ProgramError> {
-        // if the required tenure is zero (meaning we're on tier0, we simply calculate and return rewards up to this moment)
+    /// units of the gem this reward is earned on, set when the reward is reserved.
+    /// fixed rates are per unit, so reservations and accruals scale with it
+    pub gem_count: u64,
 
-        // if staking period is over
-        let tenure_expiry = self.staked_at.try_add(self.reward_tier.required_tenure)?;
+    // ----------------- variable rate only
+    /// total ever accrued to this vault, paid_out_reward is subtracted to get what's still owed
+    pub accrued_reward: u64,
 
-        msg!("calculated tenure expiry of {}", tenure_expiry);
-        msg!("self.last_rewards_claimed_at of {}", self.last_rewards_claimed_at);
+    /// vault's own copy of the farm's accrued_reward_per_rarity_point flag, as of last update
+    pub last_recorded_accrued_reward_per_rarity_point: Number128,
+}
 
-        // check if staking period is not over, or user is on tier0. in both cases, compute unclaimed rewards and send along
-        if now < tenure_expiry || self.reward_tier.required_tenure == 0 {
-            // this means the farmer is claiming rewards before the lock period is over
-            let unclaimed_rewards_time = now.try_sub(self.last_rewards_claimed_at)?;
+impl VaultReward {
+    pub fn outstanding_reward(
+        &self,
+        now: u64,
+        schedule: &FixedRateSchedule,
+    ) -> Result<u64, ProgramError> {
+        // rather than accruing from the last claim onwards (which would round down on every
+        // single claim, and slow rates would never pay out), we compute the total earned since
+        // staking at both points in time and take the difference. rounding then only ever
+        // costs the staker a fraction of a token in total
+        let earned_now = self.earned_reward(now, schedule)?;
+        let earned_at_last_claim = self.earned_reward(self.last_rewards_claimed_at, schedule)?;
+
+        msg!(
+            "earned {} as of now vs {} as of last claim at {}",
+            earned_now,
+            earned_at_last_claim,
+            self.last_rewards_claimed_at
+        );
+
+        earned_now.try_sub(earned_at_last_claim)
+    }
 
-            msg!("calculated unclaimed_rewards_time of {}", unclaimed_rewards_time);
+    /// total earned since staked_at up to `until`, both on the current tier and at tier0
+    /// rate once its tenure is over
+    pub fn earned_reward(
+        &self,
+        until: u64,
+        schedule: &FixedRateSchedule,
+    ) -> Result<u64, ProgramError> {
+        let (reserved_part, unreserved_part) = self.earned_reward_split(until, schedule)?;
 
-            let outstanding_rewards =
-                unclaimed_rewards_time.try_mul(self.computed_reward_rate(denominator)?)?;
+        reserved_part.try_add(unreserved_part)
+    }
 
-            return Ok(outstanding_rewards);
+    /// splits what was earned up to `until` into the part covered by the tier's reservation,
+    /// and the part that accrued at tier0 rate and so was never reserved:
+    /// - on tier0 (required tenure = 0) nothing is reserved, everything earned is unreserved
+    /// - on any other tier, time up to the tenure expiry is earned on that tier, anything
+    ///   after it at the farm's tier0 rate
+    pub fn earned_reward_split(
+        &self,
+        until: u64,
+        schedule: &FixedRateSchedule,
+    ) -> Result<(u64, u64), ProgramError> {
+        if until <= self.staked_at {
+            return Ok((0, 0));
         }
 
-        // in some scenarios, the last_rewards_claimed_at might be more than expiry
-        // in a unique situation where a farmer claimed tokens before expiry
-        // this claim sets the last_rewards_claimed_at
-        // after expiry the farmer attempts to claim again, which works
-        // and sets the last_rewards_claimed_at to after expiry time
-        // but the next claim is going to have some problems, because the
-        // last_rewards_claimed_at is now more than tenure_expiry and we cannot subtract them anymore
-        if self.last_rewards_claimed_at > tenure_expiry {
-            msg!("self.last_rewards_claimed_at of {} vs tenure expiry of {} resulted in zero tokens to claim", self.last_rewards_claimed_at, tenure_expiry);
-            // this is a scenario where the user has claimed tokens after expiry.
-            // meaning they have nothing else to claim
-            // so we return zero
-            return Ok(0);
-        }
+        let denominator = schedule.denominator;
 
-        let unclaimed_rewards_time = tenure_expiry.try_sub(self.last_rewards_claimed_at)?;
+        if self.reward_tier.required_tenure == 0 {
+            let earned = self.reward_tier.reward_over(
+                self.gem_count,
+                until.try_sub(self.staked_at)?,
+                denominator,
+            )?;
+            return Ok((0, earned));
+        }
 
-        let outstanding_reward = unclaimed_rewards_time.try_mul(self.reward_tier.reward_rate)?;
+        let tenure_expiry = self.tenure_expiry()?;
+
+        let reserved_part = self.reward_tier.reward_over(
+            self.gem_count,
+            std::cmp::min(until, tenure_expiry).try_sub(self.staked_at)?,
+            denominator,
+        )?;
+
+        let unreserved_part = if until > tenure_expiry {
+            schedule.tier0.reward_over(
+                self.gem_count,
+                until.try_sub(tenure_expiry)?,
+                denominator,
+            )?
+        } else {
+            0
+        };
+
+        Ok((reserved_part, unreserved_part))
+    }
 
-        msg!("unclaimed_rewards_time calculated as {}", unclaimed_rewards_time);
+    /// the part of the reservation for the current tier that hasn't been earned yet
+    pub fn unearned_reward(
+        &self,
+        now: u64,
+        schedule: &FixedRateSchedule,
+    ) -> Result<u64, ProgramError> {
+        let (reserved_part, _) = self.earned_reward_split(now, schedule)?;
 
-        Ok(outstanding_reward.try_div(denominator)?)
+        self.reserved_amount.try_sub(reserved_part)
     }
 
-    pub fn computed_reward_rate(&self, denominator: u64) -> Result<u64, ProgramError> {
-        let computed_rate = self.reward_tier.reward_rate.try_div(denominator)?;
-        msg!("Computed reward rate of {} from a denominator of {} and reward tier rate of {}", computed_rate, denominator, self.reward_tier.reward_rate);
-
-        Ok(computed_rate)
+    pub fn tenure_expiry(&self) -> Result<u64, ProgramError> {
+        // add tier required tenure to time staking started
+        self.staked_at.try_add(self.reward_tier.required_tenure)
     }
 
     pub fn claim_rewards(
         &mut self,
         pot_balance: u64,
         now: u64,
-        denominator: u64,
+        schedule: &FixedRateSchedule,
     ) -> Result<u64, ProgramError> {
-        let outstanding = self.outstanding_reward(now, denominator)?;
+        let outstanding = self.outstanding_reward(now, schedule)?;
 
         msg!("calculated outstanding rewards of {} ", outstanding);
 
@@ -91,6 +145,20 @@ impl VaultReward {
 
         Ok(outstanding)
     }
+
+    /// variable rate counterpart of claim_rewards - accruals are already settled by the farm,
+    /// so all that's left is to pay out the difference
+    pub fn claim_accrued_reward(&mut self, pot_balance: u64) -> Result<u64, ProgramError> {
+        let outstanding = self.accrued_reward.try_sub(self.paid_out_reward)?;
+
+        if outstanding > pot_balance {
+            return Err(ErrorCode::InsufficientFunding.into());
+        }
+
+        self.paid_out_reward.try_add_assign(outstanding)?;
+
+        Ok(outstanding)
+    }
 }
 
 #[repr(C)]
@@ -101,6 +169,18 @@ pub struct Vault {
 
     pub owner: Pubkey,
 
+    /// the original owner. the vault PDA is derived from it, so it stays put across transfers.
+    /// that also means once they've transferred it, the creator can't init_vault in this farm again
+    pub creator: Pubkey,
+
+    /// proposed by the owner, takes over the vault once they accept.
+    /// Pubkey::default() if there's no transfer pending
+    pub pending_owner: Pubkey,
+
+    /// set by the owner, can deposit / claim / withdraw on the owner's behalf
+    /// (gems and rewards still go to the owner). Pubkey::default() if there's none
+    pub delegate: Pubkey,
+
     /// signs off on any token transfers out of the gem boxes controlled by the vault
     pub authority: Pubkey,
 
@@ -108,15 +188,31 @@ pub struct Vault {
 
     pub authority_bump_seed: [u8; 1],
 
-    /// after depositing one gem, we'll set locked to true. This vault can no longer be used to deposit gems.
-    /// one gem, one vault
-    pub locked: bool,
+    /// number of StakedGem records in this vault (ie distinct mints),
+    /// each of which can hold any amount of its mint
+    pub gem_count: u64,
+
+    /// how many of those are staked in place, ie still sit in the owner's own token accounts.
+    /// the vault can't be transferred while there are any, see assert_no_gems_in_place
+    pub in_place_gem_count: u64,
+
+    /// sum of rarity points across this vault's staked gems
+    pub rarity_points_staked: u64,
+
+    /// if set, expired tenures roll into a new one on the same tier on the next claim / renewal
+    pub auto_renew: bool,
 
-    /// Store the mint of the gem in here. just for convenience of fetching vault data
-    pub gem_mint: Pubkey,
+    /// set by the farm manager, eg while a stolen gem is being looked into. nothing can be
+    /// claimed or withdrawn, and the vault's gems don't earn anything until it's unfrozen
+    pub frozen: bool,
 
-    // ----------------- rewards
-    pub reward_a: VaultReward,
+    pub frozen_at: u64,
+
+    /// earned, but more than the reward pots (or for tier0 accrual, pending funds) could cover
+    /// at the time. paid out by claim_rewards once the pots hold it on top of everyone else's share
+    pub reward_a_owed: u64,
+
+    pub reward_b_owed: u64,
 }
 
 impl Vault {
@@ -124,26 +220,335 @@ impl Vault {
         [self.authority_seed.as_ref(), &self.authority_bump_seed]
     }
 
-    pub fn attempting_to_break_bank(&self, now: u64) -> Result<bool, ProgramError> {
-        // add tier required tenure to time staking started
-        let time_since_staked = self
-            .reward_a
-            .reward_tier
-            .required_tenure
-            .try_add(self.reward_a.staked_at)?;
-
-        if now > time_since_staked {
-            return Ok(false);
+    pub fn assert_owner_or_delegate(&self, signer: &Pubkey) -> ProgramResult {
+        if *signer == self.owner || (self.delegate != Pubkey::default() && *signer == self.delegate)
+        {
+            return Ok(());
+        }
+
+        Err(ErrorCode::Unauthorized.into())
+    }
+
+    pub fn assert_not_frozen(&self) -> ProgramResult {
+        if self.frozen {
+            return Err(ErrorCode::VaultAccessSuspended.into());
         }
 
-        Ok(true)
+        Ok(())
     }
 
-    pub fn access_suspended(&self) -> Result<bool, ProgramError> {
-        if self.locked {
-            return Ok(true);
+    /// gems staked in place can only be thawed with their token account owner's signature.
+    /// the new owner couldn't get them back after a transfer, so they have to come out first
+    pub fn assert_no_gems_in_place(&self) -> ProgramResult {
+        if self.in_place_gem_count > 0 {
+            return Err(ErrorCode::GemsStakedInPlace.into());
         }
 
-        Ok(false)
+        Ok(())
+    }
+
+    /// pays back as much of what's owed as is available, see Farm::pay_owed_rewards.
+    /// returns the amounts of reward a and b to transfer out of the pots
+    pub fn pay_owed_rewards(
+        &mut self,
+        pot_a_available: u64,
+        pot_b_available: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        let to_pay_a = std::cmp::min(self.reward_a_owed, pot_a_available);
+        let to_pay_b = std::cmp::min(self.reward_b_owed, pot_b_available);
+
+        self.reward_a_owed.try_sub_assign(to_pay_a)?;
+        self.reward_b_owed.try_sub_assign(to_pay_b)?;
+
+        Ok((to_pay_a, to_pay_b))
+    }
+
+    /// hands the vault (and everything staked in it) over to the pending owner.
+    /// the delegate was picked by the previous owner, so it doesn't carry over
+    pub fn accept_owner(&mut self, new_owner: &Pubkey) -> ProgramResult {
+        if self.pending_owner == Pubkey::default() || *new_owner != self.pending_owner {
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        // might've been staked in place since the transfer was proposed
+        self.assert_no_gems_in_place()?;
+
+        self.owner = self.pending_owner;
+        self.pending_owner = Pubkey::default();
+        self.delegate = Pubkey::default();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    // tier0 pays 1 token/day once unlocked
+    fn schedule() -> FixedRateSchedule {
+        FixedRateSchedule {
+            tier0: TierConfig {
+                reward_rate: 1,
+                required_tenure: 0,
+            },
+            denominator: DAY,
+            ..FixedRateSchedule::default()
+        }
+    }
+
+    // an empty vault, for tests to override the fields they care about
+    fn test_vault() -> Vault {
+        Vault {
+            farm: Pubkey::default(),
+            owner: Pubkey::default(),
+            creator: Pubkey::default(),
+            pending_owner: Pubkey::default(),
+            delegate: Pubkey::default(),
+            authority: Pubkey::default(),
+            authority_seed: Pubkey::default(),
+            authority_bump_seed: [0],
+            gem_count: 0,
+            in_place_gem_count: 0,
+            rarity_points_staked: 0,
+            auto_renew: false,
+            frozen: false,
+            frozen_at: 0,
+            reward_a_owed: 0,
+            reward_b_owed: 0,
+        }
+    }
+
+    // the example from TierConfig docs: 10 tokens/day, locked for 60 days
+    fn ten_per_day_vault() -> VaultReward {
+        VaultReward {
+            staked_at: 1000,
+            last_rewards_claimed_at: 1000,
+            reward_tier: TierConfig {
+                reward_rate: 10,
+                required_tenure: 60 * DAY,
+            },
+            gem_count: 1,
+            ..VaultReward::default()
+        }
+    }
+
+    #[test]
+    fn test_slow_rate_accrues() {
+        let vault_reward = ten_per_day_vault();
+
+        assert_eq!(
+            vault_reward
+                .outstanding_reward(1000 + DAY, &schedule())
+                .unwrap(),
+            10
+        );
+        assert_eq!(
+            vault_reward
+                .outstanding_reward(1000 + 30 * DAY, &schedule())
+                .unwrap(),
+            300
+        );
+    }
+
+    #[test]
+    fn test_rounds_down() {
+        let vault_reward = ten_per_day_vault();
+
+        // 1h = 0.41 tokens, 3h = 1.25 tokens
+        assert_eq!(
+            vault_reward
+                .outstanding_reward(1000 + 3600, &schedule())
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            vault_reward
+                .outstanding_reward(1000 + 3 * 3600, &schedule())
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_frequent_claims_lose_nothing_to_rounding() {
+        let mut vault_reward = ten_per_day_vault();
+
+        // claiming every hour for a day still pays out the full 10 tokens
+        let mut total_claimed = 0;
+        for hour in 1..=24 {
+            total_claimed += vault_reward
+                .claim_rewards(u64::MAX, 1000 + hour * 3600, &schedule())
+                .unwrap();
+        }
+
+        assert_eq!(total_claimed, 10);
+        assert_eq!(vault_reward.paid_out_reward, 10);
+    }
+
+    #[test]
+    fn test_tier0_rate_after_tenure_expiry() {
+        let mut vault_reward = ten_per_day_vault();
+
+        vault_reward
+            .claim_rewards(u64::MAX, 1000 + DAY, &schedule())
+            .unwrap();
+
+        // the remaining 59 days at 10/day, then 40 days at tier0's 1/day
+        assert_eq!(
+            vault_reward
+                .outstanding_reward(1000 + 100 * DAY, &schedule())
+                .unwrap(),
+            590 + 40
+        );
+        assert_eq!(
+            vault_reward
+                .earned_reward_split(1000 + 100 * DAY, &schedule())
+                .unwrap(),
+            (600, 40)
+        );
+    }
+
+    #[test]
+    fn test_claims_straddling_expiry_lose_nothing() {
+        let mut vault_reward = ten_per_day_vault();
+
+        // half a day before and after expiry - the half token earned at tier0 rate only
+        // pays out once a whole one has accrued
+        let expiry = 1000 + 60 * DAY;
+        let mut total_claimed = vault_reward
+            .claim_rewards(u64::MAX, expiry - DAY / 2, &schedule())
+            .unwrap();
+        total_claimed += vault_reward
+            .claim_rewards(u64::MAX, expiry + DAY / 2, &schedule())
+            .unwrap();
+        assert_eq!(total_claimed, 600);
+
+        total_claimed += vault_reward
+            .claim_rewards(u64::MAX, expiry + DAY, &schedule())
+            .unwrap();
+        assert_eq!(total_claimed, 601);
+    }
+
+    #[test]
+    fn test_unearned_ignores_tier0_accrual() {
+        let vault_reward = VaultReward {
+            reserved_amount: 600,
+            ..ten_per_day_vault()
+        };
+
+        assert_eq!(
+            vault_reward
+                .unearned_reward(1000 + 20 * DAY, &schedule())
+                .unwrap(),
+            400
+        );
+        assert_eq!(
+            vault_reward
+                .unearned_reward(1000 + 100 * DAY, &schedule())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_tier0_keeps_accruing() {
+        let vault_reward = VaultReward {
+            staked_at: 1000,
+            last_rewards_claimed_at: 1000,
+            reward_tier: TierConfig {
+                reward_rate: 10,
+                required_tenure: 0,
+            },
+            gem_count: 1,
+            ..VaultReward::default()
+        };
+
+        assert_eq!(
+            vault_reward
+                .outstanding_reward(1000 + 100 * DAY, &schedule())
+                .unwrap(),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_owner_or_delegate() {
+        let owner = Pubkey::new_unique();
+        let mut vault = Vault {
+            owner,
+            creator: owner,
+            ..test_vault()
+        };
+        let hot_wallet = Pubkey::new_unique();
+
+        vault.assert_owner_or_delegate(&owner).unwrap();
+        assert!(vault.assert_owner_or_delegate(&hot_wallet).is_err());
+
+        // an unset delegate doesn't let the default key through
+        assert!(vault.assert_owner_or_delegate(&Pubkey::default()).is_err());
+
+        vault.delegate = hot_wallet;
+        vault.assert_owner_or_delegate(&owner).unwrap();
+        vault.assert_owner_or_delegate(&hot_wallet).unwrap();
+        assert!(vault
+            .assert_owner_or_delegate(&Pubkey::new_unique())
+            .is_err());
+    }
+
+    #[test]
+    fn test_accept_owner() {
+        let owner = Pubkey::new_unique();
+        let mut vault = Vault {
+            owner,
+            creator: owner,
+            delegate: Pubkey::new_unique(),
+            gem_count: 1,
+            rarity_points_staked: 1,
+            ..test_vault()
+        };
+        let new_owner = Pubkey::new_unique();
+
+        // nothing proposed yet
+        assert!(vault.accept_owner(&new_owner).is_err());
+        assert!(vault.accept_owner(&Pubkey::default()).is_err());
+
+        vault.pending_owner = new_owner;
+        assert!(vault.accept_owner(&Pubkey::new_unique()).is_err());
+
+        // a gem staked in place since the proposal holds the transfer up
+        vault.in_place_gem_count = 1;
+        assert!(vault.accept_owner(&new_owner).is_err());
+        assert_eq!(vault.owner, owner);
+        vault.in_place_gem_count = 0;
+
+        vault.accept_owner(&new_owner).unwrap();
+        assert_eq!(vault.owner, new_owner);
+        assert_eq!(vault.creator, owner);
+        assert_eq!(vault.pending_owner, Pubkey::default());
+        assert_eq!(vault.delegate, Pubkey::default());
+        assert_eq!(vault.gem_count, 1);
+
+        // can't be accepted twice
+        assert!(vault.accept_owner(&new_owner).is_err());
+    }
+
+    #[test]
+    fn test_pay_owed_rewards() {
+        let mut vault = Vault {
+            reward_a_owed: 100,
+            reward_b_owed: 50,
+            ..test_vault()
+        };
+
+        // only part of a can be covered so far
+        assert_eq!(vault.pay_owed_rewards(30, 80).unwrap(), (30, 50));
+        assert_eq!(vault.reward_a_owed, 70);
+        assert_eq!(vault.reward_b_owed, 0);
+
+        assert_eq!(vault.pay_owed_rewards(1000, 1000).unwrap(), (70, 0));
+        assert_eq!(vault.reward_a_owed, 0);
     }
 }
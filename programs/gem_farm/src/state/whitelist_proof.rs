@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use gem_common::errors::ErrorCode;
+
+bitflags::bitflags! {
+    pub struct WhitelistType: u8 {
+        const CREATOR = 1 << 0;
+        const MINT = 1 << 1;
+        const UPDATE_AUTHORITY = 1 << 2;
+        const COLLECTION = 1 << 3;
+    }
+}
+
+/// if this PDA exists, this means the address recorded below has been whitelisted by the
+/// farm recorded below, for the type(s) recorded below
+#[repr(C)]
+#[account]
+pub struct WhitelistProof {
+    /// WhitelistType bits
+    pub whitelist_type: u8,
+
+    pub whitelisted_address: Pubkey,
+
+    pub farm: Pubkey,
+}
+
+impl WhitelistProof {
+    pub fn read_type(&self) -> Result<WhitelistType, ProgramError> {
+        WhitelistType::from_bits(self.whitelist_type)
+            .ok_or_else(|| ErrorCode::InvalidParameter.into())
+    }
+
+    pub fn reset_type(&mut self, whitelist_type: WhitelistType) {
+        self.whitelist_type = whitelist_type.bits();
+    }
+
+    pub fn contains_type(&self, expected_whitelist_type: WhitelistType) -> ProgramResult {
+        if self.read_type()?.contains(expected_whitelist_type) {
+            return Ok(());
+        }
+
+        Err(ErrorCode::NotWhitelisted.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitelist_type() {
+        let mut proof = WhitelistProof {
+            whitelist_type: 0,
+            whitelisted_address: Pubkey::default(),
+            farm: Pubkey::default(),
+        };
+
+        // fresh proofs start out empty
+        assert!(proof.contains_type(WhitelistType::MINT).is_err());
+
+        proof.reset_type(WhitelistType::MINT);
+        proof.contains_type(WhitelistType::MINT).unwrap();
+        assert!(proof.contains_type(WhitelistType::CREATOR).is_err());
+
+        // an address can be whitelisted as both
+        proof.reset_type(WhitelistType::CREATOR | WhitelistType::MINT);
+        proof.contains_type(WhitelistType::CREATOR).unwrap();
+        proof.contains_type(WhitelistType::MINT).unwrap();
+
+        proof.reset_type(WhitelistType::UPDATE_AUTHORITY);
+        proof
+            .contains_type(WhitelistType::UPDATE_AUTHORITY)
+            .unwrap();
+        assert!(proof.contains_type(WhitelistType::CREATOR).is_err());
+
+        // unknown bits can't be read back
+        proof.whitelist_type = 1 << 7;
+        assert!(proof.read_type().is_err());
+    }
+}
@@ -16,6 +16,8 @@ export enum BankFlags {
 export enum WhitelistType {
   Creator = 1 << 0,
   Mint = 1 << 1,
+  UpdateAuthority = 1 << 2,
+  Collection = 1 << 3,
 }
 
 export class GemBankClient extends AccountUtils {
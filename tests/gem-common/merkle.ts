@@ -0,0 +1,54 @@
+import { PublicKey } from '@solana/web3.js';
+import { keccak_256 } from 'js-sha3';
+
+// mirrors programs/gem_farm/src/merkle.rs
+
+export function mintLeaf(mint: PublicKey, rarityPoints?: number): Buffer {
+  const data = [mint.toBuffer()];
+  if (rarityPoints !== undefined) {
+    const rarity = Buffer.alloc(2);
+    rarity.writeUInt16LE(rarityPoints);
+    data.push(rarity);
+  }
+  return Buffer.from(keccak_256.arrayBuffer(Buffer.concat(data)));
+}
+
+function hashPair(a: Buffer, b: Buffer): Buffer {
+  const [first, second] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return Buffer.from(keccak_256.arrayBuffer(Buffer.concat([first, second])));
+}
+
+export class MerkleTree {
+  // layers[0] are the leaves, the last layer holds the root
+  layers: Buffer[][];
+
+  constructor(leaves: Buffer[]) {
+    this.layers = [leaves];
+    while (this.layers[this.layers.length - 1].length > 1) {
+      const layer = this.layers[this.layers.length - 1];
+      const next = [];
+      for (let i = 0; i < layer.length; i += 2) {
+        // odd nodes out get promoted as is
+        next.push(i + 1 < layer.length ? hashPair(layer[i], layer[i + 1]) : layer[i]);
+      }
+      this.layers.push(next);
+    }
+  }
+
+  getRoot(): Buffer {
+    return this.layers[this.layers.length - 1][0];
+  }
+
+  getProof(leaf: Buffer): Buffer[] {
+    let index = this.layers[0].findIndex((l) => l.equals(leaf));
+    if (index === -1) throw new Error('leaf not in tree');
+
+    const proof = [];
+    for (const layer of this.layers.slice(0, -1)) {
+      const sibling = index % 2 === 0 ? index + 1 : index - 1;
+      if (sibling < layer.length) proof.push(layer[sibling]);
+      index = Math.floor(index / 2);
+    }
+    return proof;
+  }
+}
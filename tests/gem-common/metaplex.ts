@@ -1,4 +1,5 @@
 import { actions, programs, Wallet } from '@metaplex/js';
+import { BN } from '@project-serum/anchor';
 import { Connection, Keypair, PublicKey } from '@solana/web3.js';
 import fs from 'fs';
 import { pause } from './util';
@@ -57,6 +58,22 @@ export async function createMetadata(
   return metadata;
 }
 
+// turns the mint into a 1/1 - the edition takes over as mint and freeze authority
+export async function createMasterEdition(
+  connection: Connection,
+  wallet: Wallet,
+  editionMint: PublicKey
+) {
+  await actions.createMasterEdition({
+    connection,
+    wallet,
+    editionMint,
+    maxSupply: new BN(0),
+  });
+
+  return programs.metadata.MasterEdition.getPDA(editionMint);
+}
+
 export function readJSON(path: string) {
   return JSON.parse(fs.readFileSync(path, 'utf-8'));
 }
@@ -2,7 +2,7 @@ import * as anchor from '@project-serum/anchor';
 import { BN, Idl, Program, Wallet } from '@project-serum/anchor';
 import { Keypair, PublicKey, SystemProgram } from '@solana/web3.js';
 import { GemFarm } from '../../target/types/gem_farm';
-import { Connection } from '@metaplex/js';
+import { Connection, programs } from '@metaplex/js';
 import { isKp, stringifyPKsAndBNs } from '../gem-common/types';
 import { GemBankClient, WhitelistType } from '../gem-bank/gem-bank.client';
 import {
@@ -18,7 +18,8 @@ export const RewardType = {
 
 export interface FarmConfig {
   paperHandsTaxLamp: BN;
-  whitelistedCandyMachine?: PublicKey
+  minStakingSec: BN;
+  cooldownSec: BN;
 }
 
 export interface TierConfig {
@@ -210,7 +211,10 @@ export class GemFarmClient extends GemBankClient {
     payer: PublicKey | Keypair,
     rewardAMint: PublicKey,
     rewardAType: any, //RewardType instance
-    fixedRateScheduleA: FixedRateSchedule,
+    fixedRateScheduleA: FixedRateSchedule | null,
+    rewardBMint: PublicKey,
+    rewardBType: any, //RewardType instance
+    fixedRateScheduleB: FixedRateSchedule | null,
     farmConfig: FarmConfig
   ) {
     const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(
@@ -223,18 +227,27 @@ export class GemFarmClient extends GemBankClient {
       farm.publicKey,
       rewardAMint
     );
+    const [rewardBPot, rewardBPotBump] = await this.findRewardsPotPDA(
+      farm.publicKey,
+      rewardBMint
+    );
 
     const signers = [farm];
     if (isKp(farmManager)) signers.push(<Keypair>farmManager);
 
-    console.log('starting farm at', farm.publicKey.toBase58(), 'whitelisting candy machine', farmConfig?.whitelistedCandyMachine?.toBase58());
+    console.log('starting farm at', farm.publicKey.toBase58());
     const txSig = await this.farmProgram.rpc.initFarm(
       farmAuthBump,
       farmTreasuryBump,
       rewardAPotBump,
-      rewardAType,
-      fixedRateScheduleA,
-      {...farmConfig, whitelistedCandyMachine: farmConfig.whitelistedCandyMachine ? farmConfig.whitelistedCandyMachine : null},
+      rewardBPotBump,
+      {
+        rewardTypeA: rewardAType,
+        rewardTypeB: rewardBType,
+        rewardAFixedRewardSchedule: fixedRateScheduleA,
+        rewardBFixedRewardSchedule: fixedRateScheduleB,
+      },
+      farmConfig,
       {
         accounts: {
           farm: farm.publicKey,
@@ -246,6 +259,8 @@ export class GemFarmClient extends GemBankClient {
           payer: isKp(payer) ? (<Keypair>payer).publicKey : farmManager,
           rewardAPot,
           rewardAMint,
+          rewardBPot,
+          rewardBMint,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -261,6 +276,8 @@ export class GemFarmClient extends GemBankClient {
       farmTreasuryBump,
       rewardAPot,
       rewardAPotBump,
+      rewardBPot,
+      rewardBPotBump,
       txSig,
     };
   }
@@ -269,21 +286,27 @@ export class GemFarmClient extends GemBankClient {
     farm: PublicKey,
     farmManager: PublicKey | Keypair,
     config: FarmConfig | null = null,
-    newManager: PublicKey | null = null
+    newManager: PublicKey | null = null,
+    mintMerkleRoot: Buffer | null = null
   ) {
     const signers = [];
     if (isKp(farmManager)) signers.push(<Keypair>farmManager);
 
     console.log('updating farm');
-    const txSig = await this.farmProgram.rpc.updateFarm(config, newManager, {
-      accounts: {
-        farm,
-        farmManager: isKp(farmManager)
-          ? (<Keypair>farmManager).publicKey
-          : farmManager,
-      },
-      signers,
-    });
+    const txSig = await this.farmProgram.rpc.updateFarm(
+      config,
+      newManager,
+      mintMerkleRoot ? Array.from(mintMerkleRoot) : null,
+      {
+        accounts: {
+          farm,
+          farmManager: isKp(farmManager)
+            ? (<Keypair>farmManager).publicKey
+            : farmManager,
+        },
+        signers,
+      }
+    );
 
     return { txSig };
   }
@@ -333,16 +356,12 @@ export class GemFarmClient extends GemBankClient {
 
   // --------------------------------------- farmer ops ixs
 
-  async initVault(
-    farm: PublicKey,
-    identity: PublicKey | Keypair,
-    gemMint: PublicKey
-  ) {
+  async initVault(farm: PublicKey, identity: PublicKey | Keypair) {
     const creatorPk = isKp(identity)
       ? (<Keypair>identity).publicKey
       : <PublicKey>identity;
 
-    const [vault, vaultBump] = await this.findVaultPDA(farm, creatorPk, gemMint);
+    const [vault, vaultBump] = await this.findVaultPDA(farm, creatorPk);
 
     const signers = [];
     if (isKp(identity)) signers.push(<Keypair>identity);
@@ -352,7 +371,6 @@ export class GemFarmClient extends GemBankClient {
       accounts: {
         farm,
         vault,
-        gemMint,
         owner: creatorPk,
         payer: creatorPk,
         systemProgram: SystemProgram.programId,
@@ -367,41 +385,56 @@ export class GemFarmClient extends GemBankClient {
     farm: PublicKey,
     farmerIdentity: PublicKey | Keypair,
     rewardAMint: PublicKey,
-    gemMint: PublicKey
+    rewardBMint: PublicKey,
+    gemMints: PublicKey[],
+    claimer?: Keypair, // the vault's delegate, if not the owner
+    vaultCreator?: PublicKey // the original owner, if the vault was transferred
   ) {
     const identityPk = isKp(farmerIdentity)
       ? (<Keypair>farmerIdentity).publicKey
       : <PublicKey>farmerIdentity;
 
     const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(farm);
-    const [vault, vaultBump] = await this.findVaultPDA(farm, identityPk, gemMint);
+    const [vault, vaultBump] = await this.findVaultPDA(
+      farm,
+      vaultCreator ?? identityPk
+    );
+    const remainingAccounts = await this.stakedGemAccounts(vault, gemMints);
 
     const [potA, potABump] = await this.findRewardsPotPDA(farm, rewardAMint);
+    const [potB, potBBump] = await this.findRewardsPotPDA(farm, rewardBMint);
 
     const rewardADestination = await this.findATA(rewardAMint, identityPk);
+    const rewardBDestination = await this.findATA(rewardBMint, identityPk);
 
     const signers = [];
-    if (isKp(farmerIdentity)) signers.push(<Keypair>farmerIdentity);
+    if (claimer) signers.push(claimer);
+    else if (isKp(farmerIdentity)) signers.push(<Keypair>farmerIdentity);
 
     const txSig = await this.farmProgram.rpc.claimRewards(
       farmAuthBump,
       vaultBump,
       potABump,
+      potBBump,
       {
         accounts: {
           farm,
           farmAuthority: farmAuth,
           vault,
-          gemMint,
           owner: identityPk,
+          claimer: claimer ? claimer.publicKey : identityPk,
           rewardAPot: potA,
           rewardAMint,
           rewardADestination,
+          rewardBPot: potB,
+          rewardBMint,
+          rewardBDestination,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         },
+        remainingAccounts,
         signers,
       }
     );
@@ -411,45 +444,228 @@ export class GemFarmClient extends GemBankClient {
       farmAuthBump,
       potA,
       potABump,
+      potB,
+      potBBump,
       rewardADestination,
+      rewardBDestination,
       txSig,
     };
   }
 
-  async withdrawGemFromVault(
+  async renewTenure(
     farm: PublicKey,
+    vaultOwner: PublicKey,
+    cranker: PublicKey | Keypair,
+    rewardAMint: PublicKey,
+    rewardBMint: PublicKey,
+    gemMints: PublicKey[]
+  ) {
+    const crankerPk = isKp(cranker)
+      ? (<Keypair>cranker).publicKey
+      : <PublicKey>cranker;
+
+    const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(farm);
+    const [vault, vaultBump] = await this.findVaultPDA(farm, vaultOwner);
+    const remainingAccounts = await this.stakedGemAccounts(vault, gemMints);
+
+    const [potA, potABump] = await this.findRewardsPotPDA(farm, rewardAMint);
+    const [potB, potBBump] = await this.findRewardsPotPDA(farm, rewardBMint);
+
+    const rewardADestination = await this.findATA(rewardAMint, vaultOwner);
+    const rewardBDestination = await this.findATA(rewardBMint, vaultOwner);
+
+    const signers = [];
+    if (isKp(cranker)) signers.push(<Keypair>cranker);
+
+    console.log(`renewing tenure of vault ${vault.toBase58()}`);
+    const txSig = await this.farmProgram.rpc.renewTenure(
+      farmAuthBump,
+      vaultBump,
+      potABump,
+      potBBump,
+      {
+        accounts: {
+          farm,
+          farmAuthority: farmAuth,
+          vault,
+          owner: vaultOwner,
+          cranker: crankerPk,
+          rewardAPot: potA,
+          rewardAMint,
+          rewardADestination,
+          rewardBPot: potB,
+          rewardBMint,
+          rewardBDestination,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        },
+        remainingAccounts,
+        signers,
+      }
+    );
+
+    return { vault, rewardADestination, rewardBDestination, txSig };
+  }
+
+  async setAutoRenew(
+    farm: PublicKey,
+    vaultOwner: PublicKey | Keypair,
+    autoRenew: boolean
+  ) {
+    const owner = isKp(vaultOwner)
+      ? (<Keypair>vaultOwner).publicKey
+      : <PublicKey>vaultOwner;
+    const [vault] = await this.findVaultPDA(farm, owner);
+
+    const signers = [];
+    if (isKp(vaultOwner)) signers.push(<Keypair>vaultOwner);
+
+    const txSig = await this.farmProgram.rpc.setAutoRenew(autoRenew, {
+      accounts: {
+        vault,
+        owner,
+      },
+      signers,
+    });
+
+    return { vault, txSig };
+  }
+
+  async setVaultDelegate(
+    farm: PublicKey,
+    vaultOwner: PublicKey | Keypair,
+    delegate: PublicKey
+  ) {
+    const owner = isKp(vaultOwner)
+      ? (<Keypair>vaultOwner).publicKey
+      : <PublicKey>vaultOwner;
+    const [vault] = await this.findVaultPDA(farm, owner);
+
+    const signers = [];
+    if (isKp(vaultOwner)) signers.push(<Keypair>vaultOwner);
+
+    const txSig = await this.farmProgram.rpc.setVaultDelegate(delegate, {
+      accounts: {
+        vault,
+        owner,
+      },
+      signers,
+    });
+
+    return { vault, txSig };
+  }
+
+  // vaults keep the address derived from their creator, ownership transfers don't move them
+  async proposeVaultOwner(
+    vault: PublicKey,
     vaultOwner: Keypair,
+    newOwner: PublicKey
+  ) {
+    const txSig = await this.farmProgram.rpc.proposeVaultOwner(newOwner, {
+      accounts: {
+        vault,
+        owner: vaultOwner.publicKey,
+      },
+      signers: [vaultOwner],
+    });
+
+    return { txSig };
+  }
+
+  async acceptVaultOwner(vault: PublicKey, newOwner: Keypair) {
+    const txSig = await this.farmProgram.rpc.acceptVaultOwner({
+      accounts: {
+        vault,
+        newOwner: newOwner.publicKey,
+      },
+      signers: [newOwner],
+    });
+
+    return { txSig };
+  }
+
+  async beginUnstake(
+    farm: PublicKey,
+    vaultOwner: PublicKey | Keypair,
     gemMint: PublicKey,
-    rewardAMint: PublicKey
+    unstaker?: Keypair // the vault's delegate, if not the owner
   ) {
-    const [vault] = await this.findVaultPDA(farm, vaultOwner.publicKey, gemMint)
-    const [gemBox, gemBoxBump] = await this.findGemBoxPDA(vault);
-    const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(farm);
-    const [vaultAuth, vaultAuthBump] = await this.findVaultAuthorityPDA(vault);
-    const gemDestination = await this.findATA(gemMint, vaultOwner.publicKey)
+    const owner = isKp(vaultOwner)
+      ? (<Keypair>vaultOwner).publicKey
+      : <PublicKey>vaultOwner;
+    const [vault] = await this.findVaultPDA(farm, owner);
+    const [stakedGem] = await this.findStakedGemPDA(vault, gemMint);
     const [farmTreasury, farmTreasuryBump] = await this.findFarmTreasuryPDA(
       farm
     );
+
+    const txSig = await this.farmProgram.rpc.beginUnstake(farmTreasuryBump, {
+      accounts: {
+        farm,
+        farmTreasury,
+        vault,
+        owner,
+        unstaker: unstaker ? unstaker.publicKey : owner,
+        stakedGem,
+        systemProgram: SystemProgram.programId,
+      },
+      signers: [unstaker ?? <Keypair>vaultOwner],
+    });
+
+    return { vault, stakedGem, txSig };
+  }
+
+  async withdrawGemFromVault(
+    farm: PublicKey,
+    vaultOwner: PublicKey | Keypair,
+    gemMint: PublicKey,
+    rewardAMint: PublicKey,
+    rewardBMint: PublicKey,
+    amount: BN,
+    withdrawer?: Keypair // the vault's delegate, if not the owner
+  ) {
+    const owner = isKp(vaultOwner)
+      ? (<Keypair>vaultOwner).publicKey
+      : <PublicKey>vaultOwner;
+    const [vault] = await this.findVaultPDA(farm, owner);
+    const [gemBox, gemBoxBump] = await this.findGemBoxPDA(vault, gemMint);
+    const [stakedGem] = await this.findStakedGemPDA(vault, gemMint);
+    const [gdr] = await this.findGdrPDA(vault, gemMint);
+    const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(farm);
+    const [vaultAuth, vaultAuthBump] = await this.findVaultAuthorityPDA(vault);
+    const gemDestination = await this.findATA(gemMint, owner);
     const [rewardAPot, rewardAPotBump] = await this.findRewardsPotPDA(
       farm,
       rewardAMint
     );
-    const rewardADestination = await this.findATA(rewardAMint, vaultOwner.publicKey);
+    const rewardADestination = await this.findATA(rewardAMint, owner);
+    const [rewardBPot, rewardBPotBump] = await this.findRewardsPotPDA(
+      farm,
+      rewardBMint
+    );
+    const rewardBDestination = await this.findATA(rewardBMint, owner);
   
-    console.log(`withdrawing 1 gem from vault ${vault} on farm ${farm}`)
+    console.log(`withdrawing ${amount} gems from vault ${vault} on farm ${farm}`);
 
-    const txSig = await this.farmProgram.rpc.withdrawGem(farmAuthBump, farmTreasuryBump, vaultAuthBump, gemBoxBump, rewardAPotBump, {
+    const txSig = await this.farmProgram.rpc.withdrawGem(farmAuthBump, vaultAuthBump, gemBoxBump, rewardAPotBump, rewardBPotBump, amount, {
       accounts: {
         farm,
         gemMint,
         gemBox,
+        stakedGem,
+        gemDepositReceipt: gdr,
         vault,
-        farmTreasury,
         rewardADestination,
         rewardAMint,
         farmAuthority: farmAuth,
         rewardAPot,
-        owner: vaultOwner.publicKey,
+        rewardBDestination,
+        rewardBMint,
+        rewardBPot,
+        owner,
+        withdrawer: withdrawer ? withdrawer.publicKey : owner,
         authority: vaultAuth,
         gemDestination,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -457,15 +673,279 @@ export class GemFarmClient extends GemBankClient {
         systemProgram: SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
       },
-      signers: [vaultOwner]
+      signers: [withdrawer ?? <Keypair>vaultOwner]
     });
 
-    return {
-      txSig,
-      gemBoxBump,
-      gemBox,
-      gemDestination
-    }
+    return {
+      txSig,
+      gemBoxBump,
+      gemBox,
+      gdr,
+      gemDestination
+    }
+  }
+
+  // only works while the farm is paused, pays out no rewards
+  async emergencyWithdraw(
+    farm: PublicKey,
+    vaultOwner: PublicKey | Keypair,
+    gemMint: PublicKey,
+    withdrawer?: Keypair // the vault's delegate, if not the owner
+  ) {
+    const owner = isKp(vaultOwner)
+      ? (<Keypair>vaultOwner).publicKey
+      : <PublicKey>vaultOwner;
+    const [vault] = await this.findVaultPDA(farm, owner);
+    const [gemBox, gemBoxBump] = await this.findGemBoxPDA(vault, gemMint);
+    const [stakedGem] = await this.findStakedGemPDA(vault, gemMint);
+    const [gdr] = await this.findGdrPDA(vault, gemMint);
+    const [vaultAuth, vaultAuthBump] = await this.findVaultAuthorityPDA(vault);
+    const gemDestination = await this.findATA(gemMint, owner);
+
+    console.log(`emergency withdrawing gem ${gemMint} from vault ${vault}`);
+
+    const txSig = await this.farmProgram.rpc.emergencyWithdraw(
+      vaultAuthBump,
+      gemBoxBump,
+      {
+        accounts: {
+          farm,
+          vault,
+          owner,
+          withdrawer: withdrawer ? withdrawer.publicKey : owner,
+          authority: vaultAuth,
+          gemBox,
+          stakedGem,
+          gemDepositReceipt: gdr,
+          gemDestination,
+          gemMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        },
+        signers: [withdrawer ?? <Keypair>vaultOwner],
+      }
+    );
+
+    return { vault, gemBox, stakedGem, gdr, gemDestination, txSig };
+  }
+
+  async stakeInPlace(
+    farm: PublicKey,
+    vaultOwner: Keypair,
+    gemMint: PublicKey,
+    gemTokenAccount: PublicKey,
+    gemEdition: PublicKey,
+    tierConfigA: TierConfig | null = null,
+    tierConfigB: TierConfig | null = null,
+    autoRenew = false
+  ) {
+    const [vault] = await this.findVaultPDA(farm, vaultOwner.publicKey);
+    const [vaultAuth, vaultAuthBump] = await this.findVaultAuthorityPDA(vault);
+    const [stakedGem, stakedGemBump] = await this.findStakedGemPDA(
+      vault,
+      gemMint
+    );
+    const [gdr, gdrBump] = await this.findGdrPDA(vault, gemMint);
+    const [mintDenylistProof, mintDenylistProofBump] =
+      await this.findDenylistProofPDA(farm, gemMint);
+
+    console.log(`staking gem ${gemMint} in place, vault ${vault} on farm ${farm}`);
+
+    const txSig = await this.farmProgram.rpc.stakeInPlace(
+      vaultAuthBump,
+      stakedGemBump,
+      gdrBump,
+      mintDenylistProofBump,
+      {
+        amount: new BN(1),
+        rewardATierConfig: tierConfigA,
+        rewardBTierConfig: tierConfigB,
+        autoRenew,
+        merkleProof: [],
+        merkleRarityPoints: null,
+      },
+      {
+        accounts: {
+          farm,
+          vault,
+          owner: vaultOwner.publicKey,
+          authority: vaultAuth,
+          gemTokenAccount,
+          stakedGem,
+          gemDepositReceipt: gdr,
+          gemMint,
+          gemEdition,
+          mintDenylistProof,
+          tokenMetadataProgram: programs.metadata.MetadataProgram.PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        },
+        signers: [vaultOwner],
+      }
+    );
+
+    return { vault, vaultAuth, stakedGem, gdr, txSig };
+  }
+
+  async unstakeInPlace(
+    farm: PublicKey,
+    vaultOwner: Keypair,
+    gemMint: PublicKey,
+    gemTokenAccount: PublicKey,
+    gemEdition: PublicKey,
+    rewardAMint: PublicKey,
+    rewardBMint: PublicKey
+  ) {
+    const [vault] = await this.findVaultPDA(farm, vaultOwner.publicKey);
+    const [stakedGem] = await this.findStakedGemPDA(vault, gemMint);
+    const [gdr] = await this.findGdrPDA(vault, gemMint);
+    const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(farm);
+    const [vaultAuth, vaultAuthBump] = await this.findVaultAuthorityPDA(vault);
+    const [rewardAPot, rewardAPotBump] = await this.findRewardsPotPDA(
+      farm,
+      rewardAMint
+    );
+    const rewardADestination = await this.findATA(
+      rewardAMint,
+      vaultOwner.publicKey
+    );
+    const [rewardBPot, rewardBPotBump] = await this.findRewardsPotPDA(
+      farm,
+      rewardBMint
+    );
+    const rewardBDestination = await this.findATA(
+      rewardBMint,
+      vaultOwner.publicKey
+    );
+
+    console.log(`unstaking gem ${gemMint} in place, vault ${vault} on farm ${farm}`);
+
+    const txSig = await this.farmProgram.rpc.unstakeInPlace(
+      farmAuthBump,
+      vaultAuthBump,
+      rewardAPotBump,
+      rewardBPotBump,
+      {
+        accounts: {
+          farm,
+          farmAuthority: farmAuth,
+          vault,
+          owner: vaultOwner.publicKey,
+          authority: vaultAuth,
+          stakedGem,
+          gemDepositReceipt: gdr,
+          gemTokenAccount,
+          gemMint,
+          gemEdition,
+          rewardAPot,
+          rewardAMint,
+          rewardADestination,
+          rewardBPot,
+          rewardBMint,
+          rewardBDestination,
+          tokenMetadataProgram: programs.metadata.MetadataProgram.PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        },
+        signers: [vaultOwner],
+      }
+    );
+
+    return { vault, stakedGem, txSig };
+  }
+
+  // only works while the farm is paused, pays out no rewards
+  async emergencyUnstakeInPlace(
+    farm: PublicKey,
+    vaultOwner: Keypair,
+    gemMint: PublicKey,
+    gemTokenAccount: PublicKey,
+    gemEdition: PublicKey
+  ) {
+    const [vault] = await this.findVaultPDA(farm, vaultOwner.publicKey);
+    const [stakedGem] = await this.findStakedGemPDA(vault, gemMint);
+    const [gdr] = await this.findGdrPDA(vault, gemMint);
+    const [vaultAuth, vaultAuthBump] = await this.findVaultAuthorityPDA(vault);
+
+    console.log(`emergency unstaking gem ${gemMint} in place, vault ${vault}`);
+
+    const txSig = await this.farmProgram.rpc.emergencyUnstakeInPlace(
+      vaultAuthBump,
+      {
+        accounts: {
+          farm,
+          vault,
+          owner: vaultOwner.publicKey,
+          authority: vaultAuth,
+          stakedGem,
+          gemDepositReceipt: gdr,
+          gemTokenAccount,
+          gemMint,
+          gemEdition,
+          tokenMetadataProgram: programs.metadata.MetadataProgram.PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [vaultOwner],
+      }
+    );
+
+    return { vault, stakedGem, txSig };
+  }
+
+
+  async upgradeTier(
+    farm: PublicKey,
+    vaultOwner: PublicKey | Keypair,
+    gemMint: PublicKey,
+    rewardMint: PublicKey,
+    tierConfig: TierConfig,
+    upgrader?: Keypair // the vault's delegate, if not the owner
+  ) {
+    const owner = isKp(vaultOwner)
+      ? (<Keypair>vaultOwner).publicKey
+      : <PublicKey>vaultOwner;
+
+    const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(farm);
+    const [vault] = await this.findVaultPDA(farm, owner);
+    const [stakedGem] = await this.findStakedGemPDA(vault, gemMint);
+    const [pot, potBump] = await this.findRewardsPotPDA(farm, rewardMint);
+    const rewardDestination = await this.findATA(rewardMint, owner);
+
+    const signers = [];
+    if (upgrader) signers.push(upgrader);
+    else if (isKp(vaultOwner)) signers.push(<Keypair>vaultOwner);
+
+    console.log(`upgrading tier of gem ${gemMint.toBase58()} in vault ${vault.toBase58()}`);
+    const txSig = await this.farmProgram.rpc.upgradeTier(
+      farmAuthBump,
+      potBump,
+      tierConfig,
+      {
+        accounts: {
+          farm,
+          farmAuthority: farmAuth,
+          vault,
+          owner,
+          upgrader: upgrader ? upgrader.publicKey : owner,
+          stakedGem,
+          rewardPot: pot,
+          rewardMint,
+          rewardDestination,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        },
+        signers,
+      }
+    );
+
+    return { vault, stakedGem, pot, potBump, rewardDestination, txSig };
   }
 
   async depositGem(
@@ -473,17 +953,29 @@ export class GemFarmClient extends GemBankClient {
     vaultOwner: PublicKey | Keypair,
     gemMint: PublicKey,
     gemSource: PublicKey,
-    tierConfig: TierConfig|null,
+    amount: BN,
+    tierConfigA: TierConfig|null,
+    tierConfigB: TierConfig|null,
+    autoRenew = false,
     mintProof?: PublicKey,
     metadata?: PublicKey,
-    creatorProof?: PublicKey
+    creatorProof?: PublicKey,
+    collectionProof?: PublicKey,
+    updateAuthorityProof?: PublicKey,
+    merkleProof: Buffer[] = [],
+    merkleRarityPoints: number | null = null,
+    depositor?: Keypair // the vault's delegate, if not the owner
   ) {
     const owner = (isKp(vaultOwner)
     ? (<Keypair>vaultOwner).publicKey
     : vaultOwner) as unknown as PublicKey
-    const [vault] = await this.findVaultPDA(farm, owner, gemMint);
-    const [gemBox, gemBoxBump] = await this.findGemBoxPDA(vault);
+    const [vault] = await this.findVaultPDA(farm, owner);
+    const [gemBox, gemBoxBump] = await this.findGemBoxPDA(vault, gemMint);
+    const [stakedGem, stakedGemBump] = await this.findStakedGemPDA(vault, gemMint);
+    const [gdr, gdrBump] = await this.findGdrPDA(vault, gemMint);
     const [vaultAuth, vaultAuthBump] = await this.findVaultAuthorityPDA(vault);
+    const [mintDenylistProof, mintDenylistProofBump] =
+      await this.findDenylistProofPDA(farm, gemMint);
 
     const remainingAccounts = [];
     if (mintProof)
@@ -504,25 +996,52 @@ export class GemFarmClient extends GemBankClient {
         isWritable: false,
         isSigner: false,
       });
+    if (collectionProof)
+      remainingAccounts.push({
+        pubkey: collectionProof,
+        isWritable: false,
+        isSigner: false,
+      });
+    if (updateAuthorityProof)
+      remainingAccounts.push({
+        pubkey: updateAuthorityProof,
+        isWritable: false,
+        isSigner: false,
+      });
 
     const signers = [];
-    if (isKp(vaultOwner)) signers.push(<Keypair>vaultOwner);
+    if (depositor) signers.push(depositor);
+    else if (isKp(vaultOwner)) signers.push(<Keypair>vaultOwner);
 
     console.log(
-      `depositing 1 gems into  vault: ${vault.toBase58()} on farm: ${farm.toBase58()}`
+      `depositing ${amount} gems into  vault: ${vault.toBase58()} on farm: ${farm.toBase58()}`
     );
     const txSig = await this.farmProgram.rpc.depositGem(
       vaultAuthBump,
       gemBoxBump,
-      tierConfig,
+      stakedGemBump,
+      gdrBump,
+      mintDenylistProofBump,
+      {
+        amount,
+        rewardATierConfig: tierConfigA,
+        rewardBTierConfig: tierConfigB,
+        autoRenew,
+        merkleProof: merkleProof.map((node) => Array.from(node)),
+        merkleRarityPoints,
+      },
       {
         accounts: {
           vault,
           farm,
           owner,
+          depositor: depositor ? depositor.publicKey : owner,
           gemSource,
           gemBox,
+          stakedGem,
+          gemDepositReceipt: gdr,
           gemMint,
+          mintDenylistProof,
           authority: vaultAuth,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -537,6 +1056,8 @@ export class GemFarmClient extends GemBankClient {
       vaultAuth,
       vaultAuthBump,
       gemBox,
+      stakedGem,
+      gdr,
       vault,
       farm,
       gemBoxBump,
@@ -548,6 +1069,14 @@ export class GemFarmClient extends GemBankClient {
     return this.farmProgram.account.vault.fetch(vault);
   }
 
+  async fetchStakedGemAcc(stakedGem: PublicKey) {
+    return this.farmProgram.account.stakedGem.fetch(stakedGem);
+  }
+
+  async fetchGdrAcc(gdr: PublicKey) {
+    return this.farmProgram.account.gemDepositReceipt.fetch(gdr);
+  }
+
   async fetchAllGdrPDAs(vault?: PublicKey) {
     const filter = vault
       ? [
@@ -568,13 +1097,33 @@ export class GemFarmClient extends GemBankClient {
     return this.findProgramAddress(this.farmProgram.programId, [vault]);
   }
 
-  async findGemBoxPDA(vault: PublicKey) {
+  async findGemBoxPDA(vault: PublicKey, mint: PublicKey) {
     return this.findProgramAddress(this.farmProgram.programId, [
       'gem_box',
       vault,
+      mint,
+    ]);
+  }
+
+  async findStakedGemPDA(vault: PublicKey, mint: PublicKey) {
+    return this.findProgramAddress(this.farmProgram.programId, [
+      'staked_gem',
+      vault,
+      mint,
     ]);
   }
 
+  // claims / renewals go over whichever of the vault's staked gems are passed in
+  async stakedGemAccounts(vault: PublicKey, gemMints: PublicKey[]) {
+    return Promise.all(
+      gemMints.map(async (mint) => ({
+        pubkey: (await this.findStakedGemPDA(vault, mint))[0],
+        isWritable: true,
+        isSigner: false,
+      }))
+    );
+  }
+
   async findGdrPDA(vault: PublicKey, mint: PublicKey) {
     return this.findProgramAddress(this.farmProgram.programId, [
       'gem_deposit_receipt',
@@ -583,6 +1132,211 @@ export class GemFarmClient extends GemBankClient {
     ]);
   }
 
+  // --------------------------------------- whitelist ops ixs
+
+  async addToWhitelist(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    addressToWhitelist: PublicKey,
+    whitelistType: WhitelistType
+  ) {
+    const [whitelistProof, whitelistProofBump] =
+      await this.findWhitelistProofPDA(farm, addressToWhitelist);
+
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    console.log('adding to whitelist', addressToWhitelist.toBase58());
+    const txSig = await this.farmProgram.rpc.addToWhitelist(
+      whitelistProofBump,
+      whitelistType,
+      {
+        accounts: {
+          farm,
+          farmManager: isKp(farmManager)
+            ? (<Keypair>farmManager).publicKey
+            : farmManager,
+          addressToWhitelist,
+          whitelistProof,
+          systemProgram: SystemProgram.programId,
+        },
+        signers,
+      }
+    );
+
+    return { whitelistProof, whitelistProofBump, txSig };
+  }
+
+  async removeFromWhitelist(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    addressToRemove: PublicKey
+  ) {
+    const [whitelistProof, whitelistProofBump] =
+      await this.findWhitelistProofPDA(farm, addressToRemove);
+
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    console.log('removing from whitelist', addressToRemove.toBase58());
+    const txSig = await this.farmProgram.rpc.removeFromWhitelist(
+      whitelistProofBump,
+      {
+        accounts: {
+          farm,
+          farmManager: isKp(farmManager)
+            ? (<Keypair>farmManager).publicKey
+            : farmManager,
+          addressToRemove,
+          whitelistProof,
+        },
+        signers,
+      }
+    );
+
+    return { whitelistProof, whitelistProofBump, txSig };
+  }
+
+  async fetchWhitelistProofAcc(whitelistProof: PublicKey) {
+    return this.farmProgram.account.whitelistProof.fetch(whitelistProof);
+  }
+
+  // --------------------------------------- denylist ops ixs
+
+  async addToDenylist(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    mintToDenylist: PublicKey
+  ) {
+    const [denylistProof, denylistProofBump] =
+      await this.findDenylistProofPDA(farm, mintToDenylist);
+
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    console.log('adding to denylist', mintToDenylist.toBase58());
+    const txSig = await this.farmProgram.rpc.addToDenylist(denylistProofBump, {
+      accounts: {
+        farm,
+        farmManager: isKp(farmManager)
+          ? (<Keypair>farmManager).publicKey
+          : farmManager,
+        mintToDenylist,
+        denylistProof,
+        systemProgram: SystemProgram.programId,
+      },
+      signers,
+    });
+
+    return { denylistProof, denylistProofBump, txSig };
+  }
+
+  async removeFromDenylist(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    mintToRemove: PublicKey
+  ) {
+    const [denylistProof, denylistProofBump] =
+      await this.findDenylistProofPDA(farm, mintToRemove);
+
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    console.log('removing from denylist', mintToRemove.toBase58());
+    const txSig = await this.farmProgram.rpc.removeFromDenylist(
+      denylistProofBump,
+      {
+        accounts: {
+          farm,
+          farmManager: isKp(farmManager)
+            ? (<Keypair>farmManager).publicKey
+            : farmManager,
+          mintToRemove,
+          denylistProof,
+        },
+        signers,
+      }
+    );
+
+    return { denylistProof, denylistProofBump, txSig };
+  }
+
+  async freezeVault(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    vault: PublicKey,
+    gemMints: PublicKey[] // every gem staked in the vault
+  ) {
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    console.log('freezing vault', vault.toBase58());
+    const txSig = await this.farmProgram.rpc.freezeVault({
+      accounts: {
+        farm,
+        farmManager: isKp(farmManager)
+          ? (<Keypair>farmManager).publicKey
+          : farmManager,
+        vault,
+      },
+      remainingAccounts: await this.stakedGemAccounts(vault, gemMints),
+      signers,
+    });
+
+    return { txSig };
+  }
+
+  async unfreezeVault(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    vault: PublicKey,
+    gemMints: PublicKey[] // every gem staked in the vault
+  ) {
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    console.log('unfreezing vault', vault.toBase58());
+    const txSig = await this.farmProgram.rpc.unfreezeVault({
+      accounts: {
+        farm,
+        farmManager: isKp(farmManager)
+          ? (<Keypair>farmManager).publicKey
+          : farmManager,
+        vault,
+      },
+      remainingAccounts: await this.stakedGemAccounts(vault, gemMints),
+      signers,
+    });
+
+    return { txSig };
+  }
+
+  async setFarmPaused(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    paused: boolean
+  ) {
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    const txSig = await this.farmProgram.rpc.setFarmPaused(paused, {
+      accounts: {
+        farm,
+        farmManager: isKp(farmManager)
+          ? (<Keypair>farmManager).publicKey
+          : farmManager,
+      },
+      signers,
+    });
+
+    return { txSig };
+  }
+
+
+  async fetchDenylistProofAcc(denylistProof: PublicKey) {
+    return this.farmProgram.account.denylistProof.fetch(denylistProof);
+  }
+
   // --------------------------------------- funder ops ixs
 
   async authorizeCommon(
@@ -660,7 +1414,8 @@ export class GemFarmClient extends GemBankClient {
     rewardMint: PublicKey,
     funder: PublicKey | Keypair,
     rewardSource: PublicKey,
-    amount: BN
+    amount: BN,
+    durationSec: BN | null = null
   ) {
     const funderPk = isKp(funder)
       ? (<Keypair>funder).publicKey
@@ -679,6 +1434,7 @@ export class GemFarmClient extends GemBankClient {
       authorizationProofBump,
       potBump,
       amount,
+      durationSec,
       {
         accounts: {
           farm,
@@ -706,6 +1462,89 @@ export class GemFarmClient extends GemBankClient {
     };
   }
 
+  async cancelReward(
+    farm: PublicKey,
+    canceller: PublicKey | Keypair,
+    rewardMint: PublicKey,
+    receiver: PublicKey,
+    isFarmManager = true
+  ) {
+    const cancellerPk = isKp(canceller)
+      ? (<Keypair>canceller).publicKey
+      : <PublicKey>canceller;
+
+    const [farmAuth, farmAuthBump] = await this.findFarmAuthorityPDA(farm);
+    const [pot, potBump] = await this.findRewardsPotPDA(farm, rewardMint);
+    const rewardDestination = await this.findATA(rewardMint, receiver);
+
+    //funders need to prove they're authorized, the manager doesn't
+    const remainingAccounts = [];
+    if (!isFarmManager) {
+      const [authorizationProof] = await this.findAuthorizationProofPDA(
+        farm,
+        cancellerPk
+      );
+      remainingAccounts.push({
+        pubkey: authorizationProof,
+        isWritable: false,
+        isSigner: false,
+      });
+    }
+
+    const signers = [];
+    if (isKp(canceller)) signers.push(<Keypair>canceller);
+
+    console.log('cancelling reward', rewardMint.toBase58());
+    const txSig = await this.farmProgram.rpc.cancelReward(farmAuthBump, potBump, {
+      accounts: {
+        farm,
+        farmAuthority: farmAuth,
+        canceller: cancellerPk,
+        rewardPot: pot,
+        rewardMint,
+        rewardDestination,
+        receiver,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      },
+      remainingAccounts,
+      signers,
+    });
+
+    return {
+      farmAuth,
+      farmAuthBump,
+      pot,
+      potBump,
+      rewardDestination,
+      txSig,
+    };
+  }
+
+  async lockReward(
+    farm: PublicKey,
+    farmManager: PublicKey | Keypair,
+    rewardMint: PublicKey
+  ) {
+    const signers = [];
+    if (isKp(farmManager)) signers.push(<Keypair>farmManager);
+
+    console.log('locking reward', rewardMint.toBase58());
+    const txSig = await this.farmProgram.rpc.lockReward({
+      accounts: {
+        farm,
+        farmManager: isKp(farmManager)
+          ? (<Keypair>farmManager).publicKey
+          : farmManager,
+        rewardMint,
+      },
+      signers,
+    });
+
+    return { txSig };
+  }
 
   // --------------------------------------- helpers
 
@@ -719,20 +1558,27 @@ export class GemFarmClient extends GemBankClient {
     return Object.keys(farmer.state)[0];
   }
 
-  async findVaultPDA(farm: PublicKey, creator: PublicKey, mint: PublicKey) {
+  async findVaultPDA(farm: PublicKey, creator: PublicKey) {
     return this.findProgramAddress(this.farmProgram.programId, [
       'vault',
       farm,
       creator,
-      mint
     ]);
   }
 
-  async findWhitelistProofPDA(bank: PublicKey, whitelistedAddress: PublicKey) {
+  async findWhitelistProofPDA(farm: PublicKey, whitelistedAddress: PublicKey) {
     return this.findProgramAddress(this.farmProgram.programId, [
       'whitelist',
-      bank,
+      farm,
       whitelistedAddress,
     ]);
   }
+
+  async findDenylistProofPDA(farm: PublicKey, denylistedMint: PublicKey) {
+    return this.findProgramAddress(this.farmProgram.programId, [
+      'denylist',
+      farm,
+      denylistedMint,
+    ]);
+  }
 }
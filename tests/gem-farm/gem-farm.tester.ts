@@ -17,6 +17,7 @@ import { ITokenData } from '../gem-common/account-utils';
 import { assert } from 'chai';
 import { WhitelistType } from '../gem-bank/gem-bank.client';
 import { NodeWallet } from '../gem-common/node-wallet';
+import { createMasterEdition, createMetadata } from '../gem-common/metaplex';
 
 // --------------------------------------- configs
 
@@ -24,6 +25,8 @@ export const PRECISION = 10 ** 15;
 
 export const defaultFarmConfig = <FarmConfig>{
   paperHandsTaxLamp: new BN(0),
+  minStakingSec: new BN(0),
+  cooldownSec: new BN(0),
 };
 
 export const defaultVariableConfig = <VariableRateConfig>{
@@ -177,6 +180,19 @@ export class GemFarmTester extends GemFarmClient {
     return { gemAmount, gemOwner, gem };
   }
 
+  // a 1/1 with metadata + master edition, the only kind of gem that can be frozen in place
+  async prepNft(owner: Keypair) {
+    const gem = await this.nw.createMintAndFundATA(owner.publicKey, toBN(1));
+    await createMetadata(this.conn, this.nw.wallet, gem.tokenMint);
+    const gemEdition = await createMasterEdition(
+      this.conn,
+      this.nw.wallet,
+      gem.tokenMint
+    );
+
+    return { gem, gemEdition };
+  }
+
   // --------------------------------------- getters
 
   async fetchFarm() {
@@ -202,6 +218,9 @@ export class GemFarmTester extends GemFarmClient {
       this.rewardMint.publicKey,
       RewardType.Fixed,
       defaultFixedConfig.schedule,
+      this.rewardSecondMint.publicKey,
+      RewardType.Fixed,
+      defaultFixedConfig.schedule,
       farmConfig
     );
   }
@@ -214,16 +233,20 @@ export class GemFarmTester extends GemFarmClient {
       this.rewardMint.publicKey,
       RewardType.Fixed,
       schedule || defaultFixedConfig.schedule,
+      this.rewardSecondMint.publicKey,
+      RewardType.Fixed,
+      defaultFixedConfig.schedule,
       farmConfig
     );
   }
 
-  async callUpdateFarm(farmConfig?: FarmConfig, newManager?: PublicKey) {
+  async callUpdateFarm(farmConfig?: FarmConfig, newManager?: PublicKey, mintMerkleRoot?: Buffer) {
     return this.updateFarm(
       this.farm.publicKey,
       this.farmManager,
       farmConfig,
-      newManager
+      newManager,
+      mintMerkleRoot
     );
   }
 
@@ -247,19 +270,71 @@ export class GemFarmTester extends GemFarmClient {
 
   // ----------------- farmer
 
-  async callInitVault(identity: Keypair, token: PublicKey, farm?: PublicKey) {
-    return this.initVault(farm || this.farm.publicKey, identity, token);
+  async callInitVault(identity: Keypair, farm?: PublicKey) {
+    return this.initVault(farm || this.farm.publicKey, identity);
+  }
+
+  async callBeginUnstake(identity: Keypair, mint: PublicKey, farm?: PublicKey) {
+    return this.beginUnstake(farm || this.farm.publicKey, identity, mint)
+  }
+
+  async callWithdraw(identity: Keypair, mint: PublicKey, farm?: PublicKey, amount: Numerical = 1) {
+    return this.withdrawGemFromVault(farm || this.farm.publicKey, identity, mint, this.rewardMint.publicKey, this.rewardSecondMint.publicKey, toBN(amount))
+  }
+
+  async callEmergencyWithdraw(identity: Keypair, mint: PublicKey, farm?: PublicKey) {
+    return this.emergencyWithdraw(farm || this.farm.publicKey, identity, mint)
+  }
+
+  async callSetVaultDelegate(identity: Keypair, delegate: PublicKey, farm?: PublicKey) {
+    return this.setVaultDelegate(farm || this.farm.publicKey, identity, delegate)
+  }
+
+  async callStakeInPlace(identity: Keypair, gem: ITokenData, gemEdition: PublicKey, farm?: PublicKey) {
+    return this.stakeInPlace(farm || this.farm.publicKey, identity, gem.tokenMint, gem.tokenAcc, gemEdition)
+  }
+
+  async callUnstakeInPlace(identity: Keypair, gem: ITokenData, gemEdition: PublicKey, farm?: PublicKey) {
+    return this.unstakeInPlace(farm || this.farm.publicKey, identity, gem.tokenMint, gem.tokenAcc, gemEdition, this.rewardMint.publicKey, this.rewardSecondMint.publicKey)
   }
 
-  async callWithdraw(identity: Keypair, mint: PublicKey, farm?: PublicKey) {
-    return this.withdrawGemFromVault(farm || this.farm.publicKey, identity, mint, this.rewardMint.publicKey)
+  async callEmergencyUnstakeInPlace(identity: Keypair, gem: ITokenData, gemEdition: PublicKey, farm?: PublicKey) {
+    return this.emergencyUnstakeInPlace(farm || this.farm.publicKey, identity, gem.tokenMint, gem.tokenAcc, gemEdition)
   }
 
   async callWhitelistCreator(creator: PublicKey, farm?: PublicKey, manager?: Keypair) {
-    return this.whitelistCreator(farm || this.farm.publicKey, manager || this.farmManager, creator)
+    return this.addToWhitelist(farm || this.farm.publicKey, manager || this.farmManager, creator, WhitelistType.Creator)
   }
 
-  async callDeposit(identity: Keypair, tierSchedule: TierConfig|null = null, farm?: PublicKey) {
+  async callAddToWhitelist(address: PublicKey, whitelistType: WhitelistType, farm?: PublicKey, manager?: Keypair) {
+    return this.addToWhitelist(farm || this.farm.publicKey, manager || this.farmManager, address, whitelistType)
+  }
+
+  async callRemoveFromWhitelist(address: PublicKey, farm?: PublicKey, manager?: Keypair) {
+    return this.removeFromWhitelist(farm || this.farm.publicKey, manager || this.farmManager, address)
+  }
+
+  async callAddToDenylist(mint: PublicKey, farm?: PublicKey, manager?: Keypair) {
+    return this.addToDenylist(farm || this.farm.publicKey, manager || this.farmManager, mint)
+  }
+
+  async callRemoveFromDenylist(mint: PublicKey, farm?: PublicKey, manager?: Keypair) {
+    return this.removeFromDenylist(farm || this.farm.publicKey, manager || this.farmManager, mint)
+  }
+
+  async callFreezeVault(vault: PublicKey, gemMints: PublicKey[], farm?: PublicKey, manager?: Keypair) {
+    return this.freezeVault(farm || this.farm.publicKey, manager || this.farmManager, vault, gemMints)
+  }
+
+  async callUnfreezeVault(vault: PublicKey, gemMints: PublicKey[], farm?: PublicKey, manager?: Keypair) {
+    return this.unfreezeVault(farm || this.farm.publicKey, manager || this.farmManager, vault, gemMints)
+  }
+
+  async callSetFarmPaused(paused: boolean, farm?: PublicKey, manager?: Keypair) {
+    return this.setFarmPaused(farm || this.farm.publicKey, manager || this.farmManager, paused)
+  }
+
+  async callDeposit(identity: Keypair, tierSchedule: TierConfig|null = null, farm?: PublicKey, amount: Numerical = 1) {
     const isFarmer1 =
       identity.publicKey.toBase58() ===
       this.farmer1Identity.publicKey.toBase58();
@@ -269,7 +344,9 @@ export class GemFarmTester extends GemFarmClient {
       isFarmer1 ? this.farmer1Identity : this.farmer2Identity,
       isFarmer1 ? this.gem1.tokenMint : this.gem2.tokenMint,
       isFarmer1 ? this.gem1.tokenAcc : this.gem2.tokenAcc,
-      tierSchedule
+      toBN(amount),
+      tierSchedule,
+      null
     );
   }
 
@@ -278,7 +355,8 @@ export class GemFarmTester extends GemFarmClient {
       this.farm.publicKey,
       identity,
       this.rewardMint.publicKey,
-      gemMint
+      this.rewardSecondMint.publicKey,
+      [gemMint]
     );
   }
 
@@ -302,13 +380,31 @@ export class GemFarmTester extends GemFarmClient {
 
   // ----------------- rewards
 
-  async callFundReward(amount: BN, farm?: PublicKey) {
+  async callFundReward(amount: BN, farm?: PublicKey, durationSec?: BN) {
     return this.fundReward(
       farm || this.farm.publicKey,
       this.rewardMint.publicKey,
       this.funder,
       this.rewardSource,
-      amount
+      amount,
+      durationSec ?? null
+    );
+  }
+
+  async callCancelReward() {
+    return this.cancelReward(
+      this.farm.publicKey,
+      this.farmManager,
+      this.rewardMint.publicKey,
+      this.funder.publicKey
+    );
+  }
+
+  async callLockReward() {
+    return this.lockReward(
+      this.farm.publicKey,
+      this.farmManager,
+      this.rewardMint.publicKey
     );
   }
 
@@ -48,26 +48,26 @@ describe('claim rewards from vault', () => {
   beforeEach('preps accs', async () => {
     await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
     await gf.callInitFarm(defaultFarmConfig, fixedConfig.schedule);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
     await gf.callAuthorize();
     const amount = new BN(Math.random() * 50000);
     await gf.callFundReward(amount);
   });
 
   it('deposit gem tier2 & tier0 & tier3 & tier1 -> wait 5 seconds -> claim rewards', async () => {
-    const [{ vault: vault1Tier1 }, { vault: vault2Tier2 }] = await Promise.all([
+    const [{ stakedGem: gem1Tier1 }, { stakedGem: gem2Tier2 }] = await Promise.all([
       gf.callDeposit(gf.farmer1Identity, fixedConfig.schedule.tier1),
       gf.callDeposit(gf.farmer2Identity, fixedConfig.schedule.tier2),
     ]);
-    let vaultAcc: any = await gf.fetchVaultAcc(vault1Tier1);
+    let gemAcc: any = await gf.fetchStakedGemAcc(gem1Tier1);
 
     await pause(5000);
 
-    await gf.callClaimRewards(gf.farmer1Identity, vaultAcc.gemMint);
+    await gf.callClaimRewards(gf.farmer1Identity, gemAcc.gemMint);
 
-    // fresh vault account
-    vaultAcc = await gf.fetchVaultAcc(vault1Tier1);
+    // fresh staked gem account
+    gemAcc = await gf.fetchStakedGemAcc(gem1Tier1);
 
     const minTotalAccrued = fixedConfig.schedule.tier1?.rewardRate
       .mul(new BN(4))
@@ -75,11 +75,11 @@ describe('claim rewards from vault', () => {
     const maxTotalAccrued = fixedConfig.schedule.tier1?.rewardRate
       .mul(new BN(7))
       .toNumber()!; // waited 5 seconds have elapsed
-    const paidOutReward = vaultAcc.rewardA.paidOutReward.toNumber();
+    const paidOutReward = gemAcc.rewardA.paidOutReward.toNumber();
     const lastRewardsClaimedAt =
-      vaultAcc.rewardA.lastRewardsClaimedAt.toNumber();
+      gemAcc.rewardA.lastRewardsClaimedAt.toNumber();
     const lastRewardsUpdatedTimeDifference =
-      vaultAcc.rewardA.lastRewardsClaimedAt.sub(vaultAcc.rewardA.stakedAt);
+      gemAcc.rewardA.lastRewardsClaimedAt.sub(gemAcc.rewardA.stakedAt);
 
     assert.equal(
       true,
@@ -92,8 +92,8 @@ describe('claim rewards from vault', () => {
     ); // make sure paid out reward is between rewards for 4 seconds and rewards for 7 seconds
 
     await pause(3000);
-    await gf.callClaimRewards(gf.farmer1Identity, vaultAcc.gemMint); // claim rewards again
-    let updatedVaultAcc: any = await gf.fetchVaultAcc(vault1Tier1);
+    await gf.callClaimRewards(gf.farmer1Identity, gemAcc.gemMint); // claim rewards again
+    let updatedGemAcc: any = await gf.fetchStakedGemAcc(gem1Tier1);
 
     const minTotalAccruedB = fixedConfig.schedule.tier1?.rewardRate
       .mul(new BN(2))
@@ -101,9 +101,9 @@ describe('claim rewards from vault', () => {
     const maxTotalAccruedB = fixedConfig.schedule.tier1?.rewardRate
       .mul(new BN(4))
       .toNumber()!; // waited 3 seconds have elapsed
-    const paidOutRewardB = updatedVaultAcc.rewardA.paidOutReward.toNumber();
+    const paidOutRewardB = updatedGemAcc.rewardA.paidOutReward.toNumber();
     const lastRewardsUpdatedTimeDifferenceB =
-      updatedVaultAcc.rewardA.lastRewardsClaimedAt.toNumber() -
+      updatedGemAcc.rewardA.lastRewardsClaimedAt.toNumber() -
       lastRewardsClaimedAt; // sub from previous last rewards claimed at value (before the 3 seconds pause)
 
     const paidOutDifference = paidOutRewardB - paidOutReward; // old paid out reward minus new paid out reward should be approximately amount deposited into user's wallet
@@ -121,7 +121,7 @@ describe('claim rewards from vault', () => {
     // claim rewards for second farmer
     await gf.callClaimRewards(gf.farmer2Identity, gf.gem2.tokenMint);
 
-    let vaultAcc2: any = await gf.fetchVaultAcc(vault2Tier2);
+    let gemAcc2: any = await gf.fetchStakedGemAcc(gem2Tier2);
 
     const minTotalAccruedB2 = fixedConfig.schedule.tier2?.rewardRate
       .mul(new BN(7))
@@ -129,10 +129,10 @@ describe('claim rewards from vault', () => {
     const maxTotalAccruedB2 = fixedConfig.schedule.tier2?.rewardRate
       .mul(new BN(10))
       .toNumber()!; // waited 8 - 10 seconds have elapsed
-    const paidOutRewardB2 = vaultAcc2.rewardA.paidOutReward.toNumber();
+    const paidOutRewardB2 = gemAcc2.rewardA.paidOutReward.toNumber();
     const lastRewardsUpdatedTimeDifferenceB2 =
-      vaultAcc2.rewardA.lastRewardsClaimedAt.toNumber() -
-      vaultAcc2.rewardA.stakedAt.toNumber(); // sub from previous last rewards claimed at value (before the 3 seconds pause)
+      gemAcc2.rewardA.lastRewardsClaimedAt.toNumber() -
+      gemAcc2.rewardA.stakedAt.toNumber(); // sub from previous last rewards claimed at value (before the 3 seconds pause)
 
     assert.equal(
       true,
@@ -177,20 +177,20 @@ describe('claim rewards from vault (denominator not one)', () => {
   beforeEach('preps accs', async () => {
     await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
     await gf.callInitFarm(defaultFarmConfig, fixedConfig.schedule);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
     await gf.callAuthorize();
     const amount = new BN(Math.random() * 50000);
     await gf.callFundReward(amount);
   });
 
   it('deposit gem tier2 -> wait 5 seconds -> claim rewards', async () => {
-    const [{ vault: vault1Tier1 }, { vault: vault2Tier2 }] = await Promise.all([
+    const [{ stakedGem: gem1Tier1 }, { stakedGem: gem2Tier2 }] = await Promise.all([
       gf.callDeposit(gf.farmer1Identity, fixedConfig.schedule.tier1),
       gf.callDeposit(gf.farmer2Identity, fixedConfig.schedule.tier2),
     ]);
-    let vaultAcc: any = await gf.fetchVaultAcc(vault1Tier1);
-    let vaultAcc2: any = await gf.fetchVaultAcc(vault2Tier2);
+    let gemAcc: any = await gf.fetchStakedGemAcc(gem1Tier1);
+    let gemAcc2: any = await gf.fetchStakedGemAcc(gem2Tier2);
 
     await pause(4000);
 
@@ -198,12 +198,12 @@ describe('claim rewards from vault (denominator not one)', () => {
       { rewardADestination },
       { rewardADestination: rewardADestination2 },
     ] = await Promise.all([
-      gf.callClaimRewards(gf.farmer1Identity, vaultAcc.gemMint),
-      gf.callClaimRewards(gf.farmer2Identity, vaultAcc2.gemMint),
+      gf.callClaimRewards(gf.farmer1Identity, gemAcc.gemMint),
+      gf.callClaimRewards(gf.farmer2Identity, gemAcc2.gemMint),
     ]);
 
-    vaultAcc = await gf.fetchVaultAcc(vault1Tier1);
-    vaultAcc2 = await gf.fetchVaultAcc(vault2Tier2);
+    gemAcc = await gf.fetchStakedGemAcc(gem1Tier1);
+    gemAcc2 = await gf.fetchStakedGemAcc(gem2Tier2);
 
     const timeInSeconds = fixedConfig.schedule.tier1.requiredTenure.toNumber();
     const timeInSeconds2 = fixedConfig.schedule.tier2.requiredTenure.toNumber();
@@ -218,19 +218,19 @@ describe('claim rewards from vault (denominator not one)', () => {
 
     assert.equal(
       expectedPaidOutReward,
-      vaultAcc.rewardA.paidOutReward.toNumber()
+      gemAcc.rewardA.paidOutReward.toNumber()
     );
     assert.equal(
       expectedPaidOutReward,
-      vaultAcc.rewardA.reservedAmount.toNumber()
+      gemAcc.rewardA.reservedAmount.toNumber()
     );
     assert.equal(
       expectedPaidOutReward2,
-      vaultAcc2.rewardA.paidOutReward.toNumber()
+      gemAcc2.rewardA.paidOutReward.toNumber()
     );
     assert.equal(
       expectedPaidOutReward2,
-      vaultAcc2.rewardA.reservedAmount.toNumber()
+      gemAcc2.rewardA.reservedAmount.toNumber()
     );
 
     // check reward amounts
@@ -246,14 +246,14 @@ describe('claim rewards from vault (denominator not one)', () => {
     // the checks after this return deal with time, and are a little unstable.
     return;
     const exactTimeRewarded2 =
-      vaultAcc2.rewardA.lastRewardsClaimedAt.toNumber() -
-      vaultAcc2.rewardA.stakedAt.toNumber();
+      gemAcc2.rewardA.lastRewardsClaimedAt.toNumber() -
+      gemAcc2.rewardA.stakedAt.toNumber();
 
     // for the first farmer, they staked for 2 days only.
     // which means we only pay them that maximum to their rewards
     assert.equal(
-      vaultAcc.rewardA.rewardTier.requiredTenure.toNumber() *
-        (vaultAcc.rewardA.rewardTier.rewardRate.toNumber() /
+      gemAcc.rewardA.rewardTier.requiredTenure.toNumber() *
+        (gemAcc.rewardA.rewardTier.rewardRate.toNumber() /
           fixedConfig.schedule.denominator.toNumber()),
       rewardADestinationAcc.amount.toNumber()
     );
@@ -262,7 +262,7 @@ describe('claim rewards from vault (denominator not one)', () => {
     // which means they receive a partial payout matching the time they've staked so far.
     assert.equal(
       exactTimeRewarded2 *
-        (vaultAcc2.rewardA.rewardTier.rewardRate.toNumber() /
+        (gemAcc2.rewardA.rewardTier.rewardRate.toNumber() /
           fixedConfig.schedule.denominator.toNumber()),
       rewardADestination2Acc.amount.toNumber()
     );
@@ -275,61 +275,61 @@ describe('claim rewards from vault  (after tenure completed)', () => {
   beforeEach('preps accs', async () => {
     await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
     await gf.callInitFarm(defaultFarmConfig);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
     await gf.callAuthorize();
     const amount = new BN(Math.random() * 50000);
     await gf.callFundReward(amount);
   });
 
   it('claiming after tenure does not overpay', async () => {
-    const { vault, farm } = await gf.callDeposit(
+    const { stakedGem, farm } = await gf.callDeposit(
       gf.farmer1Identity,
       defaultFixedConfig.schedule.tier1
     );
 
-    let vaultAcc: any = await gf.fetchVaultAcc(vault);
+    let gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
 
     await pause(1000); // wait for 1s. claim rewards
 
-    await gf.callClaimRewards(gf.farmer1Identity, vaultAcc.gemMint);
+    await gf.callClaimRewards(gf.farmer1Identity, gemAcc.gemMint);
 
     await pause(5000); // wait another 5s until lock period expires
 
-    await gf.callClaimRewards(gf.farmer1Identity, vaultAcc.gemMint);
+    await gf.callClaimRewards(gf.farmer1Identity, gemAcc.gemMint);
 
     let farmAcc: any = await gf.fetchFarmAcc(farm);
-    vaultAcc = await gf.fetchVaultAcc(vault);
+    gemAcc = await gf.fetchStakedGemAcc(stakedGem);
 
     // the max that will ever be paid out is the reserved amount
     assert.equal(
-      vaultAcc.rewardA.paidOutReward.toNumber(),
-      vaultAcc.rewardA.reservedAmount.toNumber()
+      gemAcc.rewardA.paidOutReward.toNumber(),
+      gemAcc.rewardA.reservedAmount.toNumber()
     );
   });
 
   it('claim rewards on tier0 pays out everytime', async () => {
-    const [{ vault: vault1Tier1 }, { vault: vault2Tier2 }] = await Promise.all([
+    const [{ stakedGem: gem1Tier1 }, { stakedGem: gem2Tier2 }] = await Promise.all([
       gf.callDeposit(gf.farmer1Identity),
       gf.callDeposit(gf.farmer2Identity),
     ]);
-    let vaultAcc: any = await gf.fetchVaultAcc(vault1Tier1);
-    let vaultAcc2: any = await gf.fetchVaultAcc(vault2Tier2);
+    let gemAcc: any = await gf.fetchStakedGemAcc(gem1Tier1);
+    let gemAcc2: any = await gf.fetchStakedGemAcc(gem2Tier2);
 
     await pause(5000);
 
-    await gf.callClaimRewards(gf.farmer1Identity, vaultAcc.gemMint);
-    await gf.callClaimRewards(gf.farmer2Identity, vaultAcc2.gemMint);
+    await gf.callClaimRewards(gf.farmer1Identity, gemAcc.gemMint);
+    await gf.callClaimRewards(gf.farmer2Identity, gemAcc2.gemMint);
 
-    vaultAcc = await gf.fetchVaultAcc(vault1Tier1);
-    vaultAcc2 = await gf.fetchVaultAcc(vault2Tier2);
+    gemAcc = await gf.fetchStakedGemAcc(gem1Tier1);
+    gemAcc2 = await gf.fetchStakedGemAcc(gem2Tier2);
 
     const timeInSeconds =
-      vaultAcc.rewardA.lastRewardsClaimedAt.toNumber() -
-      vaultAcc.rewardA.stakedAt.toNumber();
+      gemAcc.rewardA.lastRewardsClaimedAt.toNumber() -
+      gemAcc.rewardA.stakedAt.toNumber();
     const timeInSeconds2 =
-      vaultAcc2.rewardA.lastRewardsClaimedAt.toNumber() -
-      vaultAcc2.rewardA.stakedAt.toNumber();
+      gemAcc2.rewardA.lastRewardsClaimedAt.toNumber() -
+      gemAcc2.rewardA.stakedAt.toNumber();
     const expectedPaidOutReward =
       timeInSeconds * defaultFixedConfig.schedule.tier0.rewardRate.toNumber();
     const expectedPaidOutReward2 =
@@ -337,13 +337,13 @@ describe('claim rewards from vault  (after tenure completed)', () => {
 
     assert.equal(
       expectedPaidOutReward,
-      vaultAcc.rewardA.paidOutReward.toNumber()
+      gemAcc.rewardA.paidOutReward.toNumber()
     );
     assert.equal(
       expectedPaidOutReward2,
-      vaultAcc2.rewardA.paidOutReward.toNumber()
+      gemAcc2.rewardA.paidOutReward.toNumber()
     );
-    assert.equal(vaultAcc.rewardA.reservedAmount.toNumber(), 0);
-    assert.equal(vaultAcc2.rewardA.reservedAmount.toNumber(), 0);
+    assert.equal(gemAcc.rewardA.reservedAmount.toNumber(), 0);
+    assert.equal(gemAcc2.rewardA.reservedAmount.toNumber(), 0);
   });
 });
@@ -0,0 +1,165 @@
+import chai, { assert, expect } from 'chai';
+import chaiAsPromised from 'chai-as-promised';
+import { BN } from '@project-serum/anchor';
+import { PublicKey } from '@solana/web3.js';
+import {
+  defaultFarmConfig,
+  defaultFixedConfig,
+  GemFarmTester,
+} from '../gem-farm.tester';
+import { pause } from '../../gem-common/util';
+
+chai.use(chaiAsPromised);
+
+describe('vault delegates', () => {
+  let gf = new GemFarmTester();
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callAuthorize();
+    await gf.callFundReward(new BN(10000));
+  });
+
+  // farmer2 acts on behalf of farmer1, depositing its own gem into farmer1's vault
+  async function delegatedDeposit() {
+    return gf.depositGem(
+      gf.farm.publicKey,
+      gf.farmer1Identity.publicKey,
+      gf.gem2.tokenMint,
+      gf.gem2.tokenAcc,
+      new BN(1),
+      null,
+      null,
+      false,
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+      [],
+      null,
+      gf.farmer2Identity
+    );
+  }
+
+  it('sets and clears the delegate', async () => {
+    const { vault } = await gf.callSetVaultDelegate(
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+    let vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(
+      vaultAcc.delegate.toBase58(),
+      gf.farmer2Identity.publicKey.toBase58()
+    );
+
+    await gf.callSetVaultDelegate(gf.farmer1Identity, PublicKey.default);
+    vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.delegate.toBase58(), PublicKey.default.toBase58());
+  });
+
+  it('delegate deposits, claims and withdraws to the owner', async () => {
+    await gf.callSetVaultDelegate(
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+
+    const { vault } = await delegatedDeposit();
+    let vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 1);
+
+    await pause(5000);
+
+    await gf.claim(
+      gf.farm.publicKey,
+      gf.farmer1Identity.publicKey,
+      gf.rewardMint.publicKey,
+      gf.rewardSecondMint.publicKey,
+      [gf.gem2.tokenMint],
+      gf.farmer2Identity
+    );
+    // rewards go to the owner, not the delegate
+    const rewardDest = await gf.findATA(
+      gf.rewardMint.publicKey,
+      gf.farmer1Identity.publicKey
+    );
+    const rewardAcc = await gf.fetchTokenAcc(gf.rewardMint.publicKey, rewardDest);
+    assert.isAbove(rewardAcc.amount.toNumber(), 0);
+
+    await gf.beginUnstake(
+      gf.farm.publicKey,
+      gf.farmer1Identity.publicKey,
+      gf.gem2.tokenMint,
+      gf.farmer2Identity
+    );
+    await gf.withdrawGemFromVault(
+      gf.farm.publicKey,
+      gf.farmer1Identity.publicKey,
+      gf.gem2.tokenMint,
+      gf.rewardMint.publicKey,
+      gf.rewardSecondMint.publicKey,
+      new BN(1),
+      gf.farmer2Identity
+    );
+
+    // so does the gem
+    const gemDest = await gf.findATA(
+      gf.gem2.tokenMint,
+      gf.farmer1Identity.publicKey
+    );
+    const gemAcc = await gf.fetchTokenAcc(gf.gem2.tokenMint, gemDest);
+    assert.equal(gemAcc.amount.toNumber(), 1);
+
+    vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 0);
+  });
+
+  it('delegate upgrades a gem to a higher tier', async () => {
+    await gf.callSetVaultDelegate(
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+    const { stakedGem } = await delegatedDeposit();
+
+    await gf.upgradeTier(
+      gf.farm.publicKey,
+      gf.farmer1Identity.publicKey,
+      gf.gem2.tokenMint,
+      gf.rewardMint.publicKey,
+      defaultFixedConfig.schedule.tier1!,
+      gf.farmer2Identity
+    );
+
+    const gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(
+      gemAcc.rewardA.rewardTier.requiredTenure.toNumber(),
+      defaultFixedConfig.schedule.tier1!.requiredTenure.toNumber()
+    );
+  });
+
+  it('FAILS to deposit without being the delegate', async () => {
+    await expect(delegatedDeposit()).to.be.rejectedWith('0x136');
+  });
+
+  it('FAILS to claim once the delegate is cleared', async () => {
+    await gf.callSetVaultDelegate(
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+    await delegatedDeposit();
+    await gf.callSetVaultDelegate(gf.farmer1Identity, PublicKey.default);
+
+    await expect(
+      gf.claim(
+        gf.farm.publicKey,
+        gf.farmer1Identity.publicKey,
+        gf.rewardMint.publicKey,
+        gf.rewardSecondMint.publicKey,
+        [gf.gem2.tokenMint],
+        gf.farmer2Identity
+      )
+    ).to.be.rejectedWith('0x136');
+  });
+});
@@ -27,18 +27,19 @@ describe('depositing gems into vault', () => {
   beforeEach('preps accs', async () => {
     await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
     await gf.callInitFarm(defaultFarmConfig);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
     await gf.callAuthorize();
   });
 
   it('deposits gem into a vault (no funding, zero funding reserved, tier0)', async () => {
     // change from depositing into gem to depositing into gem box vault
-    const { vault, farm } = await gf.callDeposit(gf.farmer1Identity);
+    const { vault, farm, stakedGem } = await gf.callDeposit(gf.farmer1Identity);
     const { vault: vault2 } = await gf.callDeposit(gf.farmer2Identity);
 
     const vaultAcc: any = await gf.fetchVaultAcc(vault);
     const vault2Acc: any = await gf.fetchVaultAcc(vault2);
+    const gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
     const farmAcc: any = await gf.fetchFarmAcc(farm);
 
     // make sure farm account counts correct number of vaults
@@ -63,7 +64,36 @@ describe('depositing gems into vault', () => {
       (farmAcc.rewardA as any).funds.totalAccruedToStakers.toNumber(),
       0
     );
-    assert.equal(vaultAcc.rewardA.reservedAmount.toNumber(), 0);
+    assert.equal(vaultAcc.gemCount.toNumber(), 1);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem1.tokenMint.toBase58());
+    assert.equal(gemAcc.rewardA.reservedAmount.toNumber(), 0);
+  });
+
+  it('deposits a fungible amount of a gem into a vault', async () => {
+    const { vault, farm, stakedGem, gdr } = await gf.callDeposit(
+      gf.farmer1Identity,
+      null,
+      undefined,
+      10
+    );
+
+    const vaultAcc: any = await gf.fetchVaultAcc(vault);
+    const gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
+    const gdrAcc: any = await gf.fetchGdrAcc(gdr);
+    const farmAcc: any = await gf.fetchFarmAcc(farm);
+
+    // a single staked gem record, holding all 10 units
+    assert.equal(vaultAcc.gemCount.toNumber(), 1);
+    assert.equal(gemAcc.gemCount.toNumber(), 10);
+    assert.equal(gemAcc.rewardA.gemCount.toNumber(), 10);
+    assert.equal(gdrAcc.gemCount.toNumber(), 10);
+
+    // each unit counts towards the farm's share of variable rewards
+    assert.equal(vaultAcc.rarityPointsStaked.toNumber(), 10);
+    assert.equal(farmAcc.rarityPointsStaked.toNumber(), 10);
+
+    const gemBoxAcc = await gf.fetchGemAcc(gf.gem1.tokenMint, gemAcc.gemBox);
+    assert.equal(gemBoxAcc.amount.toNumber(), 10);
   });
 
   it('deposits gem into a vault (tier1)', async () => {
@@ -97,14 +127,14 @@ describe('depositing gems into vault', () => {
       totalAmount.toNumber()
     );
 
-    const { vault } = await gf.callDeposit(
+    const { stakedGem } = await gf.callDeposit(
       gf.farmer1Identity,
       defaultFixedConfig.schedule.tier1
     );
 
     farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
 
-    let vaultAcc: any = await gf.fetchVaultAcc(vault);
+    let gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
 
     let totalAccruedToVault = defaultFixedConfig.schedule.tier1?.rewardRate.mul(
       defaultFixedConfig.schedule.tier1?.requiredTenure!
@@ -115,20 +145,20 @@ describe('depositing gems into vault', () => {
 
     // the amount reserved should match the tier
     assert.equal(
-      vaultAcc.rewardA.reservedAmount.toNumber(),
+      gemAcc.rewardA.reservedAmount.toNumber(),
       totalAccruedToVault?.toNumber(),
-      'The reserved amount on vault is incorrect.'
+      'The reserved amount on staked gem is incorrect.'
     );
-    // reward tier must be saved on vault
+    // reward tier must be saved on the staked gem
     assert.equal(
-      vaultAcc.rewardA.rewardTier.rewardRate.toNumber(),
+      gemAcc.rewardA.rewardTier.rewardRate.toNumber(),
       defaultFixedConfig.schedule.tier1?.rewardRate.toNumber(),
-      'The reward rate on vault is incorrect.'
+      'The reward rate on staked gem is incorrect.'
     );
     assert.equal(
-      vaultAcc.rewardA.rewardTier.requiredTenure.toNumber(),
+      gemAcc.rewardA.rewardTier.requiredTenure.toNumber(),
       defaultFixedConfig.schedule.tier1?.requiredTenure.toNumber(),
-      'The required tenure on vault is incorrect.'
+      'The required tenure on staked gem is incorrect.'
     );
 
     assert.equal(
@@ -138,7 +168,7 @@ describe('depositing gems into vault', () => {
     );
 
     // deposit another gem
-    const { vault: secondVault } = await gf.callDeposit(
+    const { stakedGem: secondGem } = await gf.callDeposit(
       gf.farmer2Identity,
       defaultFixedConfig.schedule.tier2
     );
@@ -152,7 +182,7 @@ describe('depositing gems into vault', () => {
       totalAccruedToVault!
     );
 
-    let secondVaultAcc: any = await gf.fetchVaultAcc(secondVault);
+    let secondGemAcc: any = await gf.fetchStakedGemAcc(secondGem);
     farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
 
     // verify total accrued on farm
@@ -162,14 +192,14 @@ describe('depositing gems into vault', () => {
       'The total (after two deposits) accrued to stakers on farm is incorrect.'
     );
 
-    // verify correct tier is recorded on vault
+    // verify correct tier is recorded on the staked gem
     assert.equal(
-      secondVaultAcc.rewardA.rewardTier.rewardRate.toNumber(),
+      secondGemAcc.rewardA.rewardTier.rewardRate.toNumber(),
       defaultFixedConfig.schedule.tier2?.rewardRate.toNumber(),
       'The reward rate on second deposited gem is incorrect'
     );
     assert.equal(
-      secondVaultAcc.rewardA.rewardTier.requiredTenure.toNumber(),
+      secondGemAcc.rewardA.rewardTier.requiredTenure.toNumber(),
       defaultFixedConfig.schedule.tier2?.requiredTenure.toNumber(),
       'The required tenure on second deposited gem is incorrect'
     );
@@ -215,14 +245,14 @@ describe('depositing gems into vault', () => {
       totalAmount.toNumber()
     );
 
-    const { vault } = await gf.callDeposit(
+    const { stakedGem } = await gf.callDeposit(
       gf.farmer1Identity,
       defaultFixedConfig.schedule.tier3
     );
 
     farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
 
-    let vaultAcc: any = await gf.fetchVaultAcc(vault);
+    let gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
 
     let totalAccruedToVault = defaultFixedConfig.schedule.tier3?.rewardRate.mul(
       defaultFixedConfig.schedule.tier3?.requiredTenure!
@@ -233,31 +263,77 @@ describe('depositing gems into vault', () => {
 
     // the amount reserved should match the tier
     assert.equal(
-      vaultAcc.rewardA.reservedAmount.toNumber(),
+      gemAcc.rewardA.reservedAmount.toNumber(),
       totalAccruedToVault?.toNumber(),
-      'The reserved amount on vault is incorrect.'
+      'The reserved amount on staked gem is incorrect.'
     );
-    // reward tier must be saved on vault
+    // reward tier must be saved on the staked gem
     assert.equal(
-      vaultAcc.rewardA.rewardTier.rewardRate.toNumber(),
+      gemAcc.rewardA.rewardTier.rewardRate.toNumber(),
       defaultFixedConfig.schedule.tier3?.rewardRate.toNumber(),
-      'The reward rate on vault is incorrect.'
+      'The reward rate on staked gem is incorrect.'
     );
     assert.equal(
-      vaultAcc.rewardA.rewardTier.requiredTenure.toNumber(),
+      gemAcc.rewardA.rewardTier.requiredTenure.toNumber(),
       defaultFixedConfig.schedule.tier3?.requiredTenure.toNumber(),
-      'The required tenure on vault is incorrect.'
+      'The required tenure on staked gem is incorrect.'
     );
   });
 
-  it('FAILS when trying to deposit twice into the same vault', async () => {
-    // deposit once
-    await gf.callDeposit(gf.farmer1Identity);
+  it('deposits several gems into the same vault', async () => {
+    const { vault, farm } = await gf.callDeposit(gf.farmer1Identity);
+    const { stakedGem } = await gf.depositGem(
+      gf.farm.publicKey,
+      gf.farmer1Identity,
+      gf.gem3.tokenMint,
+      gf.gem3.tokenAcc,
+      toBN(1),
+      null,
+      null
+    );
 
-    // attempt a second deposit
-    await expect(gf.callDeposit(gf.farmer1Identity)).to.be.rejectedWith(
-      '0x140'
+    const vaultAcc: any = await gf.fetchVaultAcc(vault);
+    const gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
+    const farmAcc: any = await gf.fetchFarmAcc(farm);
+
+    // both gems sit in the one vault, which is only counted once
+    assert.equal(vaultAcc.gemCount.toNumber(), 2);
+    assert.equal(farmAcc.vaultCount.toNumber(), 1);
+    assert.equal(gemAcc.vault.toBase58(), vault.toBase58());
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem3.tokenMint.toBase58());
+
+    // and each has a deposit receipt
+    const gdrs = await gf.fetchAllGdrPDAs(vault);
+    assert.equal(gdrs.length, 2);
+    const [gdr] = await gf.findGdrPDA(vault, gf.gem3.tokenMint);
+    const gdrAcc: any = await gf.fetchGdrAcc(gdr);
+    assert.equal(gdrAcc.gemMint.toBase58(), gf.gem3.tokenMint.toBase58());
+    assert.equal(gdrAcc.gemBoxAddress.toBase58(), gemAcc.gemBox.toBase58());
+    assert.equal(gdrAcc.gemCount.toNumber(), 1);
+  });
+
+  it('tops up a gem that is already staked', async () => {
+    // deposit once, then more of the same mint
+    await gf.callDeposit(gf.farmer1Identity);
+    const { vault, stakedGem } = await gf.callDeposit(
+      gf.farmer1Identity,
+      null,
+      undefined,
+      2
     );
+
+    // the one stake record carries all of it, and the vault still counts one gem
+    const gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemCount.toNumber(), 3);
+    assert.equal(gemAcc.rarityPoints.toNumber(), 3);
+
+    const vaultAcc: any = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 1);
+    assert.equal(vaultAcc.rarityPointsStaked.toNumber(), 3);
+
+    const [gdr] = await gf.findGdrPDA(vault, gf.gem1.tokenMint);
+    const gdrAcc: any = await gf.fetchGdrAcc(gdr);
+    assert.equal(gdrAcc.gemCount.toNumber(), 3);
   });
 });
 
@@ -291,8 +367,8 @@ describe('depositing gems into vault (denominator is not one)', () => {
   beforeEach('preps accs', async () => {
     await gf.prepAccounts(100000000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
     await gf.callInitFarm(defaultFarmConfig, customFixedConfig.schedule);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
     await gf.callAuthorize();
     const amount = new BN(Math.random() * 500000);
     await gf.callFundReward(amount);
@@ -300,17 +376,17 @@ describe('depositing gems into vault (denominator is not one)', () => {
 
   it('deposits gem into a vault (denominator is not one)', async () => {
     // change from depositing into gem to depositing into gem box vault
-    const { vault, farm } = await gf.callDeposit(
+    const { stakedGem, farm } = await gf.callDeposit(
       gf.farmer1Identity,
       customFixedConfig.schedule.tier1
     );
-    const { vault: vault2 } = await gf.callDeposit(
+    const { stakedGem: stakedGem2 } = await gf.callDeposit(
       gf.farmer2Identity,
       customFixedConfig.schedule.tier3
     );
 
-    const vaultAcc: any = await gf.fetchVaultAcc(vault);
-    const vault2Acc: any = await gf.fetchVaultAcc(vault2);
+    const gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
+    const gem2Acc: any = await gf.fetchStakedGemAcc(stakedGem2);
     const farmAcc: any = await gf.fetchFarmAcc(farm);
 
     const totalReserved =
@@ -322,9 +398,9 @@ describe('depositing gems into vault (denominator is not one)', () => {
         customFixedConfig.schedule.denominator.toNumber()) *
       customFixedConfig.schedule.tier3?.requiredTenure.toNumber()!;
 
-    assert.equal(vaultAcc.rewardA.reservedAmount.toNumber(), totalReserved);
+    assert.equal(gemAcc.rewardA.reservedAmount.toNumber(), totalReserved);
     assert.equal(
-      vault2Acc.rewardA.reservedAmount.toNumber(),
+      gem2Acc.rewardA.reservedAmount.toNumber(),
       totalReservedVault2
     );
     assert.equal(
@@ -361,8 +437,8 @@ describe('depositing gems into vault (only 2 tiers defined)', () => {
   beforeEach('preps accs', async () => {
     await gf.prepAccounts(100000000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
     await gf.callInitFarm(defaultFarmConfig, customFixedConfig.schedule);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
     await gf.callAuthorize();
     const amount = new BN(Math.random() * 500000);
     await gf.callFundReward(amount);
@@ -370,17 +446,17 @@ describe('depositing gems into vault (only 2 tiers defined)', () => {
 
   it('deposits gem into a vault (only 2 tiers defined)', async () => {
     // change from depositing into gem to depositing into gem box vault
-    const { vault, farm } = await gf.callDeposit(
+    const { stakedGem, farm } = await gf.callDeposit(
       gf.farmer1Identity,
       customFixedConfig.schedule.tier1
     );
-    const { vault: vault2 } = await gf.callDeposit(
+    const { stakedGem: stakedGem2 } = await gf.callDeposit(
       gf.farmer2Identity,
       customFixedConfig.schedule.tier3
     );
 
-    const vaultAcc: any = await gf.fetchVaultAcc(vault);
-    const vault2Acc: any = await gf.fetchVaultAcc(vault2);
+    const gemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
+    const gem2Acc: any = await gf.fetchStakedGemAcc(stakedGem2);
     const farmAcc: any = await gf.fetchFarmAcc(farm);
 
     const totalReserved =
@@ -388,6 +464,6 @@ describe('depositing gems into vault (only 2 tiers defined)', () => {
         customFixedConfig.schedule.denominator.toNumber()) *
       customFixedConfig.schedule.tier1?.requiredTenure.toNumber()!;
 
-    assert.equal(vaultAcc.rewardA.reservedAmount.toNumber(), totalReserved);
+    assert.equal(gemAcc.rewardA.reservedAmount.toNumber(), totalReserved);
   });
 });
@@ -0,0 +1,83 @@
+import chai, { assert, expect } from 'chai';
+import chaiAsPromised from 'chai-as-promised';
+import { BN } from '@project-serum/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { defaultFarmConfig, GemFarmTester } from '../gem-farm.tester';
+import { pause } from '../../gem-common/util';
+
+chai.use(chaiAsPromised);
+
+describe('manager vault freezing', () => {
+  let gf = new GemFarmTester();
+  let vault: PublicKey;
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callAuthorize();
+    await gf.callFundReward(new BN(10000));
+
+    ({ vault } = await gf.callDeposit(gf.farmer1Identity));
+  });
+
+  it('freezes and unfreezes a vault', async () => {
+    const farmBefore = await gf.fetchFarmAcc(gf.farm.publicKey);
+
+    await gf.callFreezeVault(vault, [gf.gem1.tokenMint]);
+    let vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.isTrue(vaultAcc.frozen);
+    assert.isAbove(vaultAcc.frozenAt.toNumber(), 0);
+
+    // the vault's gems stop taking a share of the farm's rewards
+    let farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(
+      farmAcc.rarityPointsStaked.toNumber(),
+      farmBefore.rarityPointsStaked.toNumber() -
+        vaultAcc.rarityPointsStaked.toNumber()
+    );
+
+    // nothing can be claimed or taken out meanwhile
+    await expect(
+      gf.callClaimRewards(gf.farmer1Identity, gf.gem1.tokenMint)
+    ).to.be.rejectedWith('0x140');
+    await expect(
+      gf.callBeginUnstake(gf.farmer1Identity, gf.gem1.tokenMint)
+    ).to.be.rejectedWith('0x140');
+
+    await gf.callUnfreezeVault(vault, [gf.gem1.tokenMint]);
+    vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.isFalse(vaultAcc.frozen);
+
+    farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(
+      farmAcc.rarityPointsStaked.toNumber(),
+      farmBefore.rarityPointsStaked.toNumber()
+    );
+
+    await pause(2000);
+    await gf.callClaimRewards(gf.farmer1Identity, gf.gem1.tokenMint);
+  });
+
+  it('FAILS to freeze without every staked gem', async () => {
+    await expect(gf.callFreezeVault(vault, [])).to.be.rejectedWith('0x12e');
+  });
+
+  it('FAILS to freeze twice', async () => {
+    await gf.callFreezeVault(vault, [gf.gem1.tokenMint]);
+    await expect(
+      gf.callFreezeVault(vault, [gf.gem1.tokenMint])
+    ).to.be.rejectedWith('0x12e');
+  });
+
+  it('FAILS to freeze if not the manager', async () => {
+    await expect(
+      gf.callFreezeVault(
+        vault,
+        [gf.gem1.tokenMint],
+        undefined,
+        Keypair.generate()
+      )
+    ).to.be.rejected;
+  });
+});
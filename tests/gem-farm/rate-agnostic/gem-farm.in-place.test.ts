@@ -0,0 +1,100 @@
+import chai, { assert, expect } from 'chai';
+import chaiAsPromised from 'chai-as-promised';
+import { PublicKey } from '@solana/web3.js';
+import { defaultFarmConfig, GemFarmTester } from '../gem-farm.tester';
+import { ITokenData } from '../../gem-common/account-utils';
+import { pause } from '../../gem-common/util';
+
+chai.use(chaiAsPromised);
+
+describe('stakes gems in place (non-custodial)', () => {
+  let gf = new GemFarmTester();
+  let nft: ITokenData;
+  let nftEdition: PublicKey;
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+
+    ({ gem: nft, gemEdition: nftEdition } = await gf.prepNft(
+      gf.farmer1Identity
+    ));
+  });
+
+  it('freezes the gem in the owner wallet', async () => {
+    const { vault, vaultAuth, stakedGem, gdr } = await gf.callStakeInPlace(
+      gf.farmer1Identity,
+      nft,
+      nftEdition
+    );
+
+    // the gem never moves
+    const tokenAcc = await gf.fetchGemAcc(nft.tokenMint, nft.tokenAcc);
+    assert.equal(tokenAcc.amount.toNumber(), 1);
+    assert.equal(
+      tokenAcc.owner.toBase58(),
+      gf.farmer1Identity.publicKey.toBase58()
+    );
+    assert.isTrue(tokenAcc.isFrozen);
+    assert.equal(tokenAcc.delegate!.toBase58(), vaultAuth.toBase58());
+
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.isTrue(gemAcc.frozenInPlace);
+    assert.equal(gemAcc.gemBox.toBase58(), nft.tokenAcc.toBase58());
+    assert.equal(gemAcc.gemCount.toNumber(), 1);
+
+    // the receipt points at the owner's token account, there being no gem box
+    const gdrAcc = await gf.fetchGdrAcc(gdr);
+    assert.equal(gdrAcc.gemBoxAddress.toBase58(), nft.tokenAcc.toBase58());
+    assert.equal(gdrAcc.gemCount.toNumber(), 1);
+
+    const vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 1);
+    assert.equal(vaultAcc.inPlaceGemCount.toNumber(), 1);
+    assert.equal(vaultAcc.rarityPointsStaked.toNumber(), 1);
+  });
+
+  it('FAILS to withdraw a gem frozen in place via withdraw_gem', async () => {
+    await gf.callStakeInPlace(gf.farmer1Identity, nft, nftEdition);
+    await pause(2000);
+    await gf.callBeginUnstake(gf.farmer1Identity, nft.tokenMint);
+
+    // there's no gem box, the staked gem points to the owner's token account
+    await expect(gf.callWithdraw(gf.farmer1Identity, nft.tokenMint)).to.be
+      .rejected;
+  });
+
+  it('thaws and revokes on unstake', async () => {
+    const { vault, stakedGem, gdr } = await gf.callStakeInPlace(
+      gf.farmer1Identity,
+      nft,
+      nftEdition
+    );
+
+    // can't skip begin_unstake
+    await expect(
+      gf.callUnstakeInPlace(gf.farmer1Identity, nft, nftEdition)
+    ).to.be.rejectedWith('0x155');
+
+    await pause(2000);
+    await gf.callBeginUnstake(gf.farmer1Identity, nft.tokenMint);
+    await gf.callUnstakeInPlace(gf.farmer1Identity, nft, nftEdition);
+
+    const tokenAcc = await gf.fetchGemAcc(nft.tokenMint, nft.tokenAcc);
+    assert.equal(tokenAcc.amount.toNumber(), 1);
+    assert.isFalse(tokenAcc.isFrozen);
+    assert.isNull(tokenAcc.delegate);
+
+    await expect(gf.fetchStakedGemAcc(stakedGem)).to.be.rejectedWith(
+      `Account does not exist ${stakedGem.toBase58()}`
+    );
+    await expect(gf.fetchGdrAcc(gdr)).to.be.rejectedWith(
+      `Account does not exist ${gdr.toBase58()}`
+    );
+    const vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 0);
+    assert.equal(vaultAcc.inPlaceGemCount.toNumber(), 0);
+    assert.equal(vaultAcc.rarityPointsStaked.toNumber(), 0);
+  });
+});
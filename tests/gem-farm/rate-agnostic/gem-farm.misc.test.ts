@@ -1,5 +1,5 @@
 import { BN } from '@project-serum/anchor';
-import { LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js';
+import { LAMPORTS_PER_SOL } from '@solana/web3.js';
 import chai, { assert } from 'chai';
 import chaiAsPromised from 'chai-as-promised';
 import {
@@ -10,8 +10,6 @@ import {
 
 chai.use(chaiAsPromised);
 
-const creator = new PublicKey('75ErM1QcGjHiPMX7oLsf9meQdGSUs4ZrwS2X8tBpsZhA');
-
 describe('misc', () => {
   let gf = new GemFarmTester();
 
@@ -24,7 +22,10 @@ describe('misc', () => {
 
     const farmAcc = (await gf.fetchFarm()) as any;
 
-    assert.equal(farmAcc.config.whitelistedCandyMachine, null)
+    assert.equal(farmAcc.whitelistedCreators, 0)
+    assert.equal(farmAcc.whitelistedMints, 0)
+    assert.equal(farmAcc.whitelistedUpdateAuthorities, 0)
+    assert.equal(farmAcc.whitelistedCollections, 0)
     assert.equal(farmAcc?.rewardA?.fixedRate.schedule.tier1.rewardRate.toNumber(), defaultFixedConfig.schedule.tier1?.rewardRate)
     assert.equal(farmAcc?.rewardA?.fixedRate.schedule.tier2.rewardRate.toNumber(), defaultFixedConfig.schedule.tier2?.rewardRate)
     assert.equal(farmAcc?.rewardA?.fixedRate.schedule.tier3.rewardRate.toNumber(), defaultFixedConfig.schedule.tier3?.rewardRate)
@@ -42,27 +43,23 @@ describe('misc', () => {
     );
   });
 
-  it('inits the farm with whitelisted candy machine -> updates farm to no whitelisted candy machine -> update paper hands tax to 0 SOL', async () => {
+  it('inits the farm with paper hands tax -> update paper hands tax to 0 SOL', async () => {
     await gf.callInitSecondFarm({
+      ...defaultFarmConfig,
       paperHandsTaxLamp: new BN(2).mul(new BN(LAMPORTS_PER_SOL)),
-      whitelistedCandyMachine: creator
     });
 
     const farmAcc = (await gf.fetchFarm2()) as any;
 
-    assert.equal(farmAcc.config.whitelistedCandyMachine.toBase58(), creator.toBase58())
-
-    assert.equal(farmAcc.config.whitelistedCandyMachine.toBase58(), creator.toBase58())
     assert.equal(farmAcc.config.paperHandsTaxLamp.toNumber(), 2000000000) // 2 billion lamports (2 sol)
 
     await gf.callUpdateFarm2({
+      ...defaultFarmConfig,
       paperHandsTaxLamp: new BN(0),
-      whitelistedCandyMachine: undefined
     })
 
     const updatedFarmAccount = (await gf.fetchFarm2()) as any;
 
-    assert.equal(updatedFarmAccount.config.whitelistedCandyMachine, null)
     assert.equal(updatedFarmAccount.config.paperHandsTaxLamp.toNumber(), 0) // 0 lamports (0 SOL)
   });
 });
@@ -0,0 +1,164 @@
+import chai, { assert, expect } from 'chai';
+import chaiAsPromised from 'chai-as-promised';
+import { BN } from '@project-serum/anchor';
+import { Keypair } from '@solana/web3.js';
+import {
+  defaultFarmConfig,
+  defaultFixedConfig,
+  GemFarmTester,
+} from '../gem-farm.tester';
+import { pause } from '../../gem-common/util';
+
+chai.use(chaiAsPromised);
+
+describe('farm pause / emergency mode', () => {
+  let gf = new GemFarmTester();
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
+    await gf.callAuthorize();
+    await gf.callFundReward(new BN(10000));
+
+    await gf.callDeposit(gf.farmer1Identity);
+  });
+
+  it('blocks deposits, claims and funding while paused', async () => {
+    await gf.callSetFarmPaused(true);
+    let farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.isTrue(farmAcc.paused);
+
+    await expect(gf.callDeposit(gf.farmer2Identity)).to.be.rejectedWith(
+      '0x15b'
+    );
+    await expect(
+      gf.callClaimRewards(gf.farmer1Identity, gf.gem1.tokenMint)
+    ).to.be.rejectedWith('0x15b');
+    await expect(gf.callFundReward(new BN(100))).to.be.rejectedWith('0x15b');
+
+    // and picks back up once unpaused
+    await gf.callSetFarmPaused(false);
+    farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.isFalse(farmAcc.paused);
+
+    await gf.callDeposit(gf.farmer2Identity);
+    await gf.callClaimRewards(gf.farmer1Identity, gf.gem1.tokenMint);
+  });
+
+  it('emergency withdraws the gem without paying rewards', async () => {
+    await gf.callSetFarmPaused(true);
+
+    const { vault, stakedGem, gemBox, gemDestination } =
+      await gf.callEmergencyWithdraw(gf.farmer1Identity, gf.gem1.tokenMint);
+
+    const gemAcc = await gf.fetchTokenAcc(gf.gem1.tokenMint, gemDestination);
+    assert.equal(gemAcc.amount.toNumber(), gf.gem1Amount.toNumber());
+
+    // no rewards were paid out
+    const rewardDest = await gf.findATA(
+      gf.rewardMint.publicKey,
+      gf.farmer1Identity.publicKey
+    );
+    await expect(
+      gf.fetchTokenAcc(gf.rewardMint.publicKey, rewardDest)
+    ).to.be.rejected;
+
+    await expect(gf.fetchStakedGemAcc(stakedGem)).to.be.rejected;
+    await expect(gf.fetchGemAcc(gf.gem1.tokenMint, gemBox)).to.be.rejected;
+
+    const vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 0);
+    assert.equal(vaultAcc.rarityPointsStaked.toNumber(), 0);
+
+    const farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.vaultCount.toNumber(), 0);
+    assert.equal(farmAcc.rarityPointsStaked.toNumber(), 0);
+  });
+
+  it('releases the reservation on emergency withdrawal', async () => {
+    await gf.callDeposit(gf.farmer2Identity, defaultFixedConfig.schedule.tier1);
+    let farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.isAbove(farmAcc.rewardA.funds.totalAccruedToStakers.toNumber(), 0);
+
+    await gf.callSetFarmPaused(true);
+    await gf.callEmergencyWithdraw(gf.farmer2Identity, gf.gem2.tokenMint);
+
+    // nothing was paid out of it, so all of it goes back to pending
+    farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.rewardA.funds.totalAccruedToStakers.toNumber(), 0);
+  });
+
+  it('emergency thaws a gem staked in place', async () => {
+    const { gem, gemEdition } = await gf.prepNft(gf.farmer2Identity);
+    const { vault, stakedGem } = await gf.callStakeInPlace(
+      gf.farmer2Identity,
+      gem,
+      gemEdition
+    );
+
+    await expect(
+      gf.callEmergencyUnstakeInPlace(gf.farmer2Identity, gem, gemEdition)
+    ).to.be.rejectedWith('0x12e');
+
+    await gf.callSetFarmPaused(true);
+    await gf.callEmergencyUnstakeInPlace(gf.farmer2Identity, gem, gemEdition);
+
+    const tokenAcc = await gf.fetchGemAcc(gem.tokenMint, gem.tokenAcc);
+    assert.equal(tokenAcc.amount.toNumber(), 1);
+    assert.isFalse(tokenAcc.isFrozen);
+    assert.isNull(tokenAcc.delegate);
+
+    await expect(gf.fetchStakedGemAcc(stakedGem)).to.be.rejected;
+
+    const vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 0);
+    assert.equal(vaultAcc.inPlaceGemCount.toNumber(), 0);
+    assert.equal(vaultAcc.rarityPointsStaked.toNumber(), 0);
+  });
+
+  it('blocks regular withdrawals while paused', async () => {
+    const { gem, gemEdition } = await gf.prepNft(gf.farmer2Identity);
+    await gf.callStakeInPlace(gf.farmer2Identity, gem, gemEdition);
+
+    await pause(2000);
+    await gf.callBeginUnstake(gf.farmer1Identity, gf.gem1.tokenMint);
+    await gf.callBeginUnstake(gf.farmer2Identity, gem.tokenMint);
+
+    // those pay out rewards, the emergency instructions are the way out while paused
+    await gf.callSetFarmPaused(true);
+    await expect(
+      gf.callWithdraw(
+        gf.farmer1Identity,
+        gf.gem1.tokenMint,
+        undefined,
+        gf.gem1Amount
+      )
+    ).to.be.rejectedWith('0x15b');
+    await expect(
+      gf.callUnstakeInPlace(gf.farmer2Identity, gem, gemEdition)
+    ).to.be.rejectedWith('0x15b');
+
+    await gf.callSetFarmPaused(false);
+    await gf.callWithdraw(
+      gf.farmer1Identity,
+      gf.gem1.tokenMint,
+      undefined,
+      gf.gem1Amount
+    );
+    await gf.callUnstakeInPlace(gf.farmer2Identity, gem, gemEdition);
+  });
+
+  it('FAILS to emergency withdraw while not paused', async () => {
+    await expect(
+      gf.callEmergencyWithdraw(gf.farmer1Identity, gf.gem1.tokenMint)
+    ).to.be.rejectedWith('0x12e');
+  });
+
+  it('FAILS to pause if not the manager', async () => {
+    await expect(
+      gf.callSetFarmPaused(true, undefined, Keypair.generate())
+    ).to.be.rejected;
+  });
+});
@@ -0,0 +1,151 @@
+import chai, { assert, expect } from 'chai';
+import chaiAsPromised from 'chai-as-promised';
+import { BN } from '@project-serum/anchor';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { defaultFarmConfig, GemFarmTester } from '../gem-farm.tester';
+import { pause } from '../../gem-common/util';
+
+chai.use(chaiAsPromised);
+
+describe('vault ownership transfer', () => {
+  let gf = new GemFarmTester();
+  let vault: PublicKey;
+  let stakedGem: PublicKey;
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callAuthorize();
+    await gf.callFundReward(new BN(10000));
+
+    ({ vault, stakedGem } = await gf.callDeposit(gf.farmer1Identity));
+  });
+
+  it('transfers the vault with its gems and rewards', async () => {
+    const before = await gf.fetchStakedGemAcc(stakedGem);
+
+    await gf.proposeVaultOwner(
+      vault,
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+    let vaultAcc = await gf.fetchVaultAcc(vault);
+    // nothing changes until accepted
+    assert.equal(
+      vaultAcc.owner.toBase58(),
+      gf.farmer1Identity.publicKey.toBase58()
+    );
+    assert.equal(
+      vaultAcc.pendingOwner.toBase58(),
+      gf.farmer2Identity.publicKey.toBase58()
+    );
+
+    await gf.acceptVaultOwner(vault, gf.farmer2Identity);
+    vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(
+      vaultAcc.owner.toBase58(),
+      gf.farmer2Identity.publicKey.toBase58()
+    );
+    assert.equal(
+      vaultAcc.creator.toBase58(),
+      gf.farmer1Identity.publicKey.toBase58()
+    );
+    assert.equal(vaultAcc.pendingOwner.toBase58(), PublicKey.default.toBase58());
+    assert.equal(vaultAcc.gemCount.toNumber(), 1);
+
+    // tenure is untouched
+    const after = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(
+      after.rewardA.stakedAt.toNumber(),
+      before.rewardA.stakedAt.toNumber()
+    );
+
+    await pause(5000);
+
+    // accrued rewards go to the new owner
+    await gf.claim(
+      gf.farm.publicKey,
+      gf.farmer2Identity,
+      gf.rewardMint.publicKey,
+      gf.rewardSecondMint.publicKey,
+      [gf.gem1.tokenMint],
+      undefined,
+      gf.farmer1Identity.publicKey
+    );
+    const rewardDest = await gf.findATA(
+      gf.rewardMint.publicKey,
+      gf.farmer2Identity.publicKey
+    );
+    const rewardAcc = await gf.fetchTokenAcc(gf.rewardMint.publicKey, rewardDest);
+    assert.isAbove(rewardAcc.amount.toNumber(), 0);
+
+    // and the previous owner is locked out
+    await expect(
+      gf.callClaimRewards(gf.farmer1Identity, gf.gem1.tokenMint)
+    ).to.be.rejected;
+  });
+
+  it('FAILS to accept if not the proposed owner', async () => {
+    await gf.proposeVaultOwner(
+      vault,
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+
+    await expect(
+      gf.acceptVaultOwner(vault, Keypair.generate())
+    ).to.be.rejectedWith('0x136');
+  });
+
+  it('FAILS to accept a cancelled transfer', async () => {
+    await gf.proposeVaultOwner(
+      vault,
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+    await gf.proposeVaultOwner(vault, gf.farmer1Identity, PublicKey.default);
+
+    await expect(
+      gf.acceptVaultOwner(vault, gf.farmer2Identity)
+    ).to.be.rejectedWith('0x136');
+  });
+
+  it('FAILS to transfer while a gem is staked in place', async () => {
+    const { gem, gemEdition } = await gf.prepNft(gf.farmer1Identity);
+    await gf.callStakeInPlace(gf.farmer1Identity, gem, gemEdition);
+
+    // the gem sits in farmer1's wallet, farmer2 could never thaw it
+    await expect(
+      gf.proposeVaultOwner(
+        vault,
+        gf.farmer1Identity,
+        gf.farmer2Identity.publicKey
+      )
+    ).to.be.rejectedWith('0x15c');
+
+    // fine again once it's been unstaked
+    await pause(2000);
+    await gf.callBeginUnstake(gf.farmer1Identity, gem.tokenMint);
+    await gf.callUnstakeInPlace(gf.farmer1Identity, gem, gemEdition);
+    await gf.proposeVaultOwner(
+      vault,
+      gf.farmer1Identity,
+      gf.farmer2Identity.publicKey
+    );
+    await gf.acceptVaultOwner(vault, gf.farmer2Identity);
+
+    const vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.inPlaceGemCount.toNumber(), 0);
+    assert.equal(
+      vaultAcc.owner.toBase58(),
+      gf.farmer2Identity.publicKey.toBase58()
+    );
+  });
+
+  it('FAILS to propose if not the owner', async () => {
+    await expect(
+      gf.proposeVaultOwner(vault, gf.farmer2Identity, gf.farmer2Identity.publicKey)
+    ).to.be.rejected;
+  });
+});
@@ -0,0 +1,539 @@
+import chai, { assert, expect } from 'chai';
+import chaiAsPromised from 'chai-as-promised';
+import { defaultFarmConfig, GemFarmTester } from '../gem-farm.tester';
+import { WhitelistType } from '../../gem-bank/gem-bank.client';
+import { toBN } from '../../gem-common/types';
+import { createMetadata } from '../../gem-common/metaplex';
+import { MerkleTree, mintLeaf } from '../../gem-common/merkle';
+import { ITokenData } from '../../gem-common/account-utils';
+import { Keypair } from '@solana/web3.js';
+
+chai.use(chaiAsPromised);
+
+describe('mint whitelists', () => {
+  let gf = new GemFarmTester();
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
+  });
+
+  it('adds / removes a mint to / from the whitelist', async () => {
+    const { whitelistProof } = await gf.callAddToWhitelist(
+      gf.gem1.tokenMint,
+      WhitelistType.Mint
+    );
+
+    const proofAcc = await gf.fetchWhitelistProofAcc(whitelistProof);
+    assert.equal(proofAcc.whitelistType, WhitelistType.Mint);
+    assert.equal(
+      proofAcc.whitelistedAddress.toBase58(),
+      gf.gem1.tokenMint.toBase58()
+    );
+    assert.equal(proofAcc.farm.toBase58(), gf.farm.publicKey.toBase58());
+
+    let farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.whitelistedMints, 1);
+
+    // whitelisting again doesn't double count
+    await gf.callAddToWhitelist(gf.gem1.tokenMint, WhitelistType.Mint);
+    farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.whitelistedMints, 1);
+
+    await gf.callRemoveFromWhitelist(gf.gem1.tokenMint);
+
+    farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.whitelistedMints, 0);
+    await expect(
+      gf.fetchWhitelistProofAcc(whitelistProof)
+    ).to.be.rejectedWith('Account does not exist');
+  });
+
+  it('deposits a whitelisted mint', async () => {
+    const { whitelistProof } = await gf.callAddToWhitelist(
+      gf.gem1.tokenMint,
+      WhitelistType.Mint
+    );
+
+    const { stakedGem } = await gf.depositGem(
+      gf.farm.publicKey,
+      gf.farmer1Identity,
+      gf.gem1.tokenMint,
+      gf.gem1.tokenAcc,
+      toBN(1),
+      null,
+      null,
+      false,
+      whitelistProof
+    );
+
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem1.tokenMint.toBase58());
+  });
+
+  it('FAILS to deposit a mint that is not whitelisted', async () => {
+    await gf.callAddToWhitelist(gf.gem1.tokenMint, WhitelistType.Mint);
+
+    // gem2's proof doesn't exist
+    const [missingProof] = await gf.findWhitelistProofPDA(
+      gf.farm.publicKey,
+      gf.gem2.tokenMint
+    );
+    await expect(
+      gf.depositGem(
+        gf.farm.publicKey,
+        gf.farmer2Identity,
+        gf.gem2.tokenMint,
+        gf.gem2.tokenAcc,
+        toBN(1),
+        null,
+        null,
+        false,
+        missingProof
+      )
+    ).to.be.rejectedWith('0x142');
+
+    // and gem1's proof can't be reused for gem2
+    const [gem1Proof] = await gf.findWhitelistProofPDA(
+      gf.farm.publicKey,
+      gf.gem1.tokenMint
+    );
+    await expect(
+      gf.depositGem(
+        gf.farm.publicKey,
+        gf.farmer2Identity,
+        gf.gem2.tokenMint,
+        gf.gem2.tokenAcc,
+        toBN(1),
+        null,
+        null,
+        false,
+        gem1Proof
+      )
+    ).to.be.rejectedWith('0x142');
+  });
+
+  it('deposits anything once the last whitelisted mint is removed', async () => {
+    await gf.callAddToWhitelist(gf.gem1.tokenMint, WhitelistType.Mint);
+    await gf.callRemoveFromWhitelist(gf.gem1.tokenMint);
+
+    const { stakedGem } = await gf.callDeposit(gf.farmer2Identity);
+
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem2.tokenMint.toBase58());
+  });
+});
+
+describe('creator whitelists', () => {
+  let gf = new GemFarmTester();
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+  });
+
+  it('deposits a gem with a whitelisted, verified creator', async () => {
+    // gems are minted by the provider wallet, which can sign off as their creator
+    const creator = gf.nw.wallet.publicKey;
+    const metadata = await createMetadata(gf.conn, gf.nw.wallet, gf.gem1.tokenMint, 5, 3);
+
+    const { whitelistProof } = await gf.callWhitelistCreator(creator);
+
+    const farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.whitelistedCreators, 1);
+
+    const { stakedGem } = await gf.depositGem(
+      gf.farm.publicKey,
+      gf.farmer1Identity,
+      gf.gem1.tokenMint,
+      gf.gem1.tokenAcc,
+      toBN(1),
+      null,
+      null,
+      false,
+      undefined,
+      metadata,
+      whitelistProof
+    );
+
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem1.tokenMint.toBase58());
+  });
+
+  it('FAILS to deposit a gem where the whitelisted creator is unverified', async () => {
+    const creator = gf.nw.wallet.publicKey;
+    const metadata = await createMetadata(gf.conn, gf.nw.wallet, gf.gem1.tokenMint, 5, 1, true);
+
+    const { whitelistProof } = await gf.callWhitelistCreator(creator);
+
+    await expect(
+      gf.depositGem(
+        gf.farm.publicKey,
+        gf.farmer1Identity,
+        gf.gem1.tokenMint,
+        gf.gem1.tokenAcc,
+        toBN(1),
+        null,
+        null,
+        false,
+        undefined,
+        metadata,
+        whitelistProof
+      )
+    ).to.be.rejectedWith('0x142');
+  });
+
+  it('FAILS to deposit a gem by a creator that is not whitelisted', async () => {
+    const metadata = await createMetadata(gf.conn, gf.nw.wallet, gf.gem1.tokenMint);
+
+    // some other creator is whitelisted
+    const { whitelistProof } = await gf.callWhitelistCreator(gf.farmManager.publicKey);
+
+    await expect(
+      gf.depositGem(
+        gf.farm.publicKey,
+        gf.farmer1Identity,
+        gf.gem1.tokenMint,
+        gf.gem1.tokenAcc,
+        toBN(1),
+        null,
+        null,
+        false,
+        undefined,
+        metadata,
+        whitelistProof
+      )
+    ).to.be.rejectedWith('0x142');
+  });
+});
+
+describe('update authority whitelists', () => {
+  let gf = new GemFarmTester();
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+  });
+
+  it('deposits a gem with a whitelisted update authority', async () => {
+    // metadata is created by the provider wallet, which stays its update authority
+    const updateAuthority = gf.nw.wallet.publicKey;
+    const metadata = await createMetadata(gf.conn, gf.nw.wallet, gf.gem1.tokenMint);
+
+    const { whitelistProof } = await gf.callAddToWhitelist(
+      updateAuthority,
+      WhitelistType.UpdateAuthority
+    );
+
+    const farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.whitelistedUpdateAuthorities, 1);
+
+    const { stakedGem } = await gf.depositGem(
+      gf.farm.publicKey,
+      gf.farmer1Identity,
+      gf.gem1.tokenMint,
+      gf.gem1.tokenAcc,
+      toBN(1),
+      null,
+      null,
+      false,
+      undefined,
+      metadata,
+      undefined,
+      undefined,
+      whitelistProof
+    );
+
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem1.tokenMint.toBase58());
+  });
+
+  it('FAILS to deposit a gem under a different update authority', async () => {
+    const metadata = await createMetadata(gf.conn, gf.nw.wallet, gf.gem1.tokenMint);
+
+    // some other update authority is whitelisted
+    const { whitelistProof } = await gf.callAddToWhitelist(
+      gf.farmManager.publicKey,
+      WhitelistType.UpdateAuthority
+    );
+
+    await expect(
+      gf.depositGem(
+        gf.farm.publicKey,
+        gf.farmer1Identity,
+        gf.gem1.tokenMint,
+        gf.gem1.tokenAcc,
+        toBN(1),
+        null,
+        null,
+        false,
+        undefined,
+        metadata,
+        undefined,
+        undefined,
+        whitelistProof
+      )
+    ).to.be.rejectedWith('0x157');
+  });
+
+  it('deposits by update authority when the creator check fails', async () => {
+    const metadata = await createMetadata(gf.conn, gf.nw.wallet, gf.gem1.tokenMint);
+
+    // creator whitelisted isn't on the gem, but its update authority is whitelisted
+    const { whitelistProof: creatorProof } = await gf.callWhitelistCreator(
+      gf.farmManager.publicKey
+    );
+    const { whitelistProof: updateAuthorityProof } =
+      await gf.callAddToWhitelist(
+        gf.nw.wallet.publicKey,
+        WhitelistType.UpdateAuthority
+      );
+
+    const { stakedGem } = await gf.depositGem(
+      gf.farm.publicKey,
+      gf.farmer1Identity,
+      gf.gem1.tokenMint,
+      gf.gem1.tokenAcc,
+      toBN(1),
+      null,
+      null,
+      false,
+      undefined,
+      metadata,
+      creatorProof,
+      undefined,
+      updateAuthorityProof
+    );
+
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem1.tokenMint.toBase58());
+  });
+});
+
+describe('collection whitelists', () => {
+  let gf = new GemFarmTester();
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+  });
+
+  it('adds / removes a collection to / from the whitelist', async () => {
+    const collection = Keypair.generate().publicKey;
+
+    await gf.callAddToWhitelist(collection, WhitelistType.Collection);
+    let farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.whitelistedCollections, 1);
+
+    await gf.callRemoveFromWhitelist(collection);
+    farmAcc = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.equal(farmAcc.whitelistedCollections, 0);
+  });
+
+  it('FAILS to deposit a gem that is not part of a collection', async () => {
+    // metadata created by the test helpers predates collections, so it has none
+    const metadata = await createMetadata(gf.conn, gf.nw.wallet, gf.gem1.tokenMint);
+
+    const { whitelistProof } = await gf.callAddToWhitelist(
+      Keypair.generate().publicKey,
+      WhitelistType.Collection
+    );
+
+    await expect(
+      gf.depositGem(
+        gf.farm.publicKey,
+        gf.farmer1Identity,
+        gf.gem1.tokenMint,
+        gf.gem1.tokenAcc,
+        toBN(1),
+        null,
+        null,
+        false,
+        undefined,
+        metadata,
+        undefined,
+        whitelistProof
+      )
+    ).to.be.rejectedWith('0x142');
+  });
+});
+
+describe('merkle allowlists', () => {
+  let gf = new GemFarmTester();
+  let tree: MerkleTree;
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
+
+    // gem1 comes with a rarity weight, gem2 doesn't, the rest are filler
+    const leaves = [mintLeaf(gf.gem1.tokenMint, 5), mintLeaf(gf.gem2.tokenMint)];
+    for (let i = 0; i < 5; i++) {
+      leaves.push(mintLeaf(Keypair.generate().publicKey));
+    }
+    tree = new MerkleTree(leaves);
+
+    await gf.callUpdateFarm(undefined, undefined, tree.getRoot());
+  });
+
+  function depositWithProof(
+    identity: Keypair,
+    gem: ITokenData,
+    proof: Buffer[],
+    rarityPoints: number | null = null
+  ) {
+    return gf.depositGem(
+      gf.farm.publicKey,
+      identity,
+      gem.tokenMint,
+      gem.tokenAcc,
+      toBN(1),
+      null,
+      null,
+      false,
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+      proof,
+      rarityPoints
+    );
+  }
+
+  it('deposits allowlisted mints, weighted by rarity', async () => {
+    const farmAcc: any = await gf.fetchFarmAcc(gf.farm.publicKey);
+    assert.deepEqual(Buffer.from(farmAcc.mintMerkleRoot), tree.getRoot());
+
+    const { vault } = await depositWithProof(
+      gf.farmer1Identity,
+      gf.gem1,
+      tree.getProof(mintLeaf(gf.gem1.tokenMint, 5)),
+      5
+    );
+    const { vault: vault2 } = await depositWithProof(
+      gf.farmer2Identity,
+      gf.gem2,
+      tree.getProof(mintLeaf(gf.gem2.tokenMint))
+    );
+
+    const vaultAcc: any = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.rarityPointsStaked.toNumber(), 5);
+    const vault2Acc: any = await gf.fetchVaultAcc(vault2);
+    assert.equal(vault2Acc.rarityPointsStaked.toNumber(), 1);
+  });
+
+  it('FAILS to deposit with a made up rarity', async () => {
+    await expect(
+      depositWithProof(
+        gf.farmer1Identity,
+        gf.gem1,
+        tree.getProof(mintLeaf(gf.gem1.tokenMint, 5)),
+        50
+      )
+    ).to.be.rejectedWith('0x143');
+
+    // gem2 has no rarity in the tree, so can't claim one either
+    await expect(
+      depositWithProof(
+        gf.farmer2Identity,
+        gf.gem2,
+        tree.getProof(mintLeaf(gf.gem2.tokenMint)),
+        5
+      )
+    ).to.be.rejectedWith('0x143');
+  });
+
+  it('FAILS to deposit a mint outside the tree', async () => {
+    // gem1's proof doesn't work for gem2
+    await expect(
+      depositWithProof(
+        gf.farmer2Identity,
+        gf.gem2,
+        tree.getProof(mintLeaf(gf.gem1.tokenMint, 5))
+      )
+    ).to.be.rejectedWith('0x143');
+
+    // and without a proof it has to be on one of the other whitelists
+    await expect(
+      depositWithProof(gf.farmer2Identity, gf.gem2, [])
+    ).to.be.rejectedWith('0x142');
+  });
+
+  it('deposits anything once the root is cleared', async () => {
+    await gf.callUpdateFarm(undefined, undefined, Buffer.alloc(32));
+
+    const { stakedGem } = await gf.callDeposit(gf.farmer2Identity);
+
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem2.tokenMint.toBase58());
+  });
+});
+
+describe('mint denylists', () => {
+  let gf = new GemFarmTester();
+
+  beforeEach('preps accs', async () => {
+    await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
+    await gf.callInitFarm(defaultFarmConfig);
+    await gf.callInitVault(gf.farmer1Identity);
+  });
+
+  it('adds / removes a mint to / from the denylist', async () => {
+    const { denylistProof } = await gf.callAddToDenylist(gf.gem1.tokenMint);
+
+    const proofAcc = await gf.fetchDenylistProofAcc(denylistProof);
+    assert.equal(
+      proofAcc.denylistedMint.toBase58(),
+      gf.gem1.tokenMint.toBase58()
+    );
+    assert.equal(proofAcc.farm.toBase58(), gf.farm.publicKey.toBase58());
+
+    await gf.callRemoveFromDenylist(gf.gem1.tokenMint);
+    await expect(
+      gf.fetchDenylistProofAcc(denylistProof)
+    ).to.be.rejectedWith('Account does not exist');
+  });
+
+  it('FAILS to deposit a denylisted mint, even if whitelisted', async () => {
+    const { whitelistProof } = await gf.callAddToWhitelist(
+      gf.gem1.tokenMint,
+      WhitelistType.Mint
+    );
+    await gf.callAddToDenylist(gf.gem1.tokenMint);
+
+    await expect(
+      gf.depositGem(
+        gf.farm.publicKey,
+        gf.farmer1Identity,
+        gf.gem1.tokenMint,
+        gf.gem1.tokenAcc,
+        toBN(1),
+        null,
+        null,
+        false,
+        whitelistProof
+      )
+    ).to.be.rejectedWith('0x144');
+  });
+
+  it('deposits a mint again once taken off the denylist', async () => {
+    await gf.callAddToDenylist(gf.gem1.tokenMint);
+    await expect(gf.callDeposit(gf.farmer1Identity)).to.be.rejectedWith(
+      '0x144'
+    );
+
+    await gf.callRemoveFromDenylist(gf.gem1.tokenMint);
+
+    const { stakedGem } = await gf.callDeposit(gf.farmer1Identity);
+    const gemAcc = await gf.fetchStakedGemAcc(stakedGem);
+    assert.equal(gemAcc.gemMint.toBase58(), gf.gem1.tokenMint.toBase58());
+  });
+});
@@ -24,8 +24,8 @@ describe('withdraws gems from vault', () => {
   beforeEach('preps accs', async () => {
     await gf.prepAccounts(10000000, gf.randomInt(1, 3), 0.1); // 0.1 for rewardA
     await gf.callInitFarm(defaultFarmConfig);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint);
+    await gf.callInitVault(gf.farmer1Identity);
+    await gf.callInitVault(gf.farmer2Identity);
     await gf.callAuthorize();
     const amount = new BN(Math.random() * 50000)
     await gf.callFundReward(amount)
@@ -33,7 +33,7 @@ describe('withdraws gems from vault', () => {
 
   it('deposit gem -> wait 5 seconds -> withdraw gem (tier0)', async () => {
     // change from depositing into gem to depositing into gem box vault
-    const { vault,farm } = await gf.callDeposit(gf.farmer1Identity);
+    const { vault, farm, stakedGem } = await gf.callDeposit(gf.farmer1Identity);
     await gf.callDeposit(gf.farmer2Identity);
 
     const gemDestination = await gf.findATA(gf.gem1.tokenMint, gf.farmer1Identity.publicKey)
@@ -41,7 +41,7 @@ describe('withdraws gems from vault', () => {
     const farmAccount = await gf.fetchFarmAcc(farm)
     const prevAccount = await gf.fetchTokenAcc(gf.gem1.tokenMint, gemDestination)
 
-    const [gemBoxPDA] = await gf.findGemBoxPDA(vault)
+    const [gemBoxPDA] = await gf.findGemBoxPDA(vault, gf.gem1.tokenMint)
 
     const gemBoxPDAAccount = await gf.fetchGemAcc(gf.gem1.tokenMint, gemBoxPDA)
 
@@ -50,11 +50,12 @@ describe('withdraws gems from vault', () => {
 
     await pause(5000)
 
-    const vaultAcc = await gf.fetchVaultAcc(vault);
+    const stakedGemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
 
     // also make sure 
 
-    await gf.callWithdraw(gf.farmer1Identity, vaultAcc.gemMint)
+    await gf.callBeginUnstake(gf.farmer1Identity, stakedGemAcc.gemMint)
+    await gf.callWithdraw(gf.farmer1Identity, stakedGemAcc.gemMint)
 
     const updatedFarmAccount = await gf.fetchFarmAcc(farm)
 
@@ -69,30 +70,70 @@ describe('withdraws gems from vault', () => {
       gf.fetchGemAcc(gf.gem1.tokenMint, gemBoxPDA)
     ).to.be.rejectedWith('Failed to find account');
 
-    // make sure vault is closed after withdrawal
-    await expect( gf.fetchVaultAcc(vault)).to.be.rejectedWith(`Account does not exist ${vault.toBase58()}`);
+    // make sure the staked gem record is closed, the (now empty) vault stays open
+    await expect(gf.fetchStakedGemAcc(stakedGem)).to.be.rejectedWith(`Account does not exist ${stakedGem.toBase58()}`);
+    const vaultAcc = await gf.fetchVaultAcc(vault);
+    assert.equal(vaultAcc.gemCount.toNumber(), 0)
+    // the pots were funded, so nothing is left owed
+    assert.equal(vaultAcc.rewardAOwed.toNumber(), 0)
+    assert.equal(vaultAcc.rewardBOwed.toNumber(), 0)
+
+    // as is the deposit receipt
+    const [gdr] = await gf.findGdrPDA(vault, gf.gem1.tokenMint)
+    await expect(gf.fetchGdrAcc(gdr)).to.be.rejectedWith(`Account does not exist ${gdr.toBase58()}`);
+    assert.equal((await gf.fetchAllGdrPDAs(vault)).length, 0)
   });
+  it('deposit fungible gems -> withdraw them in several goes', async () => {
+    const { vault, farm, stakedGem, gemBox, gdr } = await gf.callDeposit(gf.farmer1Identity, null, undefined, 10);
+
+    const farmAcc = await gf.fetchFarmAcc(farm)
+
+    await gf.callBeginUnstake(gf.farmer1Identity, gf.gem1.tokenMint)
+
+    // can't take out more than was staked
+    await expect(gf.callWithdraw(gf.farmer1Identity, gf.gem1.tokenMint, undefined, 11)).to.be.rejectedWith('0x12e')
+
+    await gf.callWithdraw(gf.farmer1Identity, gf.gem1.tokenMint, undefined, 4)
+
+    // the rest is still in the vault
+    const stakedGemAcc: any = await gf.fetchStakedGemAcc(stakedGem)
+    assert.equal(stakedGemAcc.gemCount.toNumber(), 6)
+    assert.equal((await gf.fetchGemAcc(gf.gem1.tokenMint, gemBox)).amount.toNumber(), 6)
+    assert.equal((await gf.fetchGdrAcc(gdr)).gemCount.toNumber(), 6)
+    assert.equal((await gf.fetchVaultAcc(vault)).gemCount.toNumber(), 1)
+
+    await gf.callWithdraw(gf.farmer1Identity, gf.gem1.tokenMint, undefined, 6)
+
+    // and now it's all out
+    await expect(gf.fetchStakedGemAcc(stakedGem)).to.be.rejectedWith(`Account does not exist ${stakedGem.toBase58()}`);
+    await expect(gf.fetchGemAcc(gf.gem1.tokenMint, gemBox)).to.be.rejectedWith('Failed to find account');
+    assert.equal((await gf.fetchVaultAcc(vault)).gemCount.toNumber(), 0)
+    assert.equal((await gf.fetchFarmAcc(farm)).vaultCount.toNumber(), farmAcc.vaultCount.toNumber() - 1)
+  })
+
   it('deposit gem (with paper hands tax) -> wait 5 seconds -> withdraw gem (tier3) -> break bank -> farm reserved updated', async () => {
     // Prep second farm
     const farmConfig = {
+      ...defaultFarmConfig,
       paperHandsTaxLamp: new BN(LAMPORTS_PER_SOL).mul(new BN(5)) // paper hands tax is 5 sol
     }
 
     await gf.callInitSecondFarm(farmConfig);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint, gf.farm2.publicKey);
-    await gf.callInitVault(gf.farmer2Identity, gf.gem2.tokenMint, gf.farm2.publicKey);
+    await gf.callInitVault(gf.farmer1Identity, gf.farm2.publicKey);
+    await gf.callInitVault(gf.farmer2Identity, gf.farm2.publicKey);
     await gf.callAuthorize(gf.farm2.publicKey, gf.farmManager2);
     const amount = new BN(Math.random() * 500000)
     await gf.callFundReward(amount, gf.farm2.publicKey)
 
-    const { vault } = await gf.callDeposit(gf.farmer1Identity, defaultFixedConfig.schedule.tier3, gf.farm2.publicKey); // requires at least 6 seconds of staking
+    const { stakedGem } = await gf.callDeposit(gf.farmer1Identity, defaultFixedConfig.schedule.tier3, gf.farm2.publicKey); // requires at least 6 seconds of staking
     await gf.callDeposit(gf.farmer2Identity, defaultFixedConfig.schedule.tier2, gf.farm2.publicKey); // requires at least 6 seconds of staking
 
 
     const farmAcc: any = await gf.fetchFarmAcc(gf.farm2.publicKey)
-    const vaultAcc = await gf.fetchVaultAcc(vault)
+    const stakedGemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
 
-    await gf.callWithdraw(gf.farmer1Identity, vaultAcc.gemMint, gf.farm2.publicKey)
+    await gf.callBeginUnstake(gf.farmer1Identity, stakedGemAcc.gemMint, gf.farm2.publicKey)
+    await gf.callWithdraw(gf.farmer1Identity, stakedGemAcc.gemMint, gf.farm2.publicKey)
 
     const updatedFarmAcc: any = await gf.fetchFarmAcc(gf.farm2.publicKey)
 
@@ -101,9 +142,9 @@ describe('withdraws gems from vault', () => {
   })
 
   it('deposit gem -> wait 5 seconds -> withdraw gem (tier3) -> attempt to break bank (no paper hands configured)', async () => {
-    const { vault } = await gf.callDeposit(gf.farmer1Identity, defaultFixedConfig.schedule.tier3); // requires at least 6 seconds of staking
+    const { vault, stakedGem } = await gf.callDeposit(gf.farmer1Identity, defaultFixedConfig.schedule.tier3); // requires at least 6 seconds of staking
 
-    const [gemBoxPDA] = await gf.findGemBoxPDA(vault)
+    const [gemBoxPDA] = await gf.findGemBoxPDA(vault, gf.gem1.tokenMint)
 
     const gemBoxPDAAccount = await gf.fetchGemAcc(gf.gem1.tokenMint, gemBoxPDA)
 
@@ -112,30 +153,31 @@ describe('withdraws gems from vault', () => {
 
     await pause(4000) // stake for 4 seconds. now change mind on staking. withdraw.
 
-    const vaultAcc: any = await gf.fetchVaultAcc(vault);
+    const stakedGemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
 
-    await expect(gf.callWithdraw(gf.farmer1Identity, vaultAcc.gemMint)).to.be.rejectedWith('0x134')
+    await expect(gf.callBeginUnstake(gf.farmer1Identity, stakedGemAcc.gemMint)).to.be.rejectedWith('0x134')
   })
 
   it('deposit gem  (into farm with paper hands tax)-> wait 5 seconds -> withdraw gem (tier3) -> successfully break bank', async () => {
     // Prep second farm
     const farmConfig = {
+      ...defaultFarmConfig,
       paperHandsTaxLamp: new BN(LAMPORTS_PER_SOL).mul(new BN(5)) // paper hands tax is 5 sol
     }
 
     await gf.callInitSecondFarm(farmConfig);
-    await gf.callInitVault(gf.farmer1Identity, gf.gem1.tokenMint, gf.farm2.publicKey);
+    await gf.callInitVault(gf.farmer1Identity, gf.farm2.publicKey);
     await gf.callAuthorize(gf.farm2.publicKey, gf.farmManager2);
     const amount = new BN(Math.random() * 500000)
     await gf.callFundReward(amount, gf.farm2.publicKey)
 
-    const { vault, farm } = await gf.callDeposit(gf.farmer1Identity, defaultFixedConfig.schedule.tier3, gf.farm2.publicKey); // requires at least 6 seconds of staking
+    const { vault, farm, stakedGem } = await gf.callDeposit(gf.farmer1Identity, defaultFixedConfig.schedule.tier3, gf.farm2.publicKey); // requires at least 6 seconds of staking
 
     const gemDestination = await gf.findATA(gf.gem1.tokenMint, gf.farmer1Identity.publicKey)
 
     const prevAccount = await gf.fetchTokenAcc(gf.gem1.tokenMint, gemDestination)
     
-    const [gemBoxPDA] = await gf.findGemBoxPDA(vault)
+    const [gemBoxPDA] = await gf.findGemBoxPDA(vault, gf.gem1.tokenMint)
 
     const gemBoxPDAAccount = await gf.fetchGemAcc(gf.gem1.tokenMint, gemBoxPDA)
 
@@ -144,11 +186,12 @@ describe('withdraws gems from vault', () => {
 
     await pause(4000) // stake for 4 seconds. now change mind on staking. withdraw.
 
-    const vaultAcc: any = await gf.fetchVaultAcc(vault);
+    const stakedGemAcc: any = await gf.fetchStakedGemAcc(stakedGem);
 
     const farmAcc = await gf.fetchFarmAcc(farm)
 
-    await gf.callWithdraw(gf.farmer1Identity, vaultAcc.gemMint, gf.farm2.publicKey)
+    await gf.callBeginUnstake(gf.farmer1Identity, stakedGemAcc.gemMint, gf.farm2.publicKey)
+    await gf.callWithdraw(gf.farmer1Identity, stakedGemAcc.gemMint, gf.farm2.publicKey)
 
     const updatedFarmAcc = await gf.fetchFarmAcc(farm)
 